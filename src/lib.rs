@@ -2,12 +2,26 @@
 //!
 //! - Use [`InterfaceBuilder`] and [`PeerBuilder`] for interface/peers creation.
 //! - Use [`Interface`]'s and [`Peer`]'s [`std::fmt::Display`] for exporting  Wireguard config (`.to_string()`, [`write!()`], etc).
+//! - Use [`str::parse()`] for importing [`Interface`] from wg-quick config, and [`ConfigDocument`]
+//!   for editing configs without losing comments, unknown keys and ordering.
+//! - Use [`Interface::to_networkd()`] for exporting systemd-networkd `.netdev`/`.network` files.
+//! - Use [`Interface::to_nmconnection()`] and [`Interface::from_nmconnection()`] for
+//!   NetworkManager keyfiles.
+//! - Use [`Interface::to_routeros()`] for exporting MikroTik RouterOS scripts.
+//! - Use [`Interface::to_uci()`] and [`Interface::from_uci()`] for OpenWrt UCI configs.
 //! - Use [`PrivateKey`] and [`PublicKey`] for generating, importing keys.
 //! - Use [`AmneziaSettings`] for generating/using AmneziaWG obfuscation values.
 //!
 //! # Features
 //!
 //! - `amneziawg`: Adds AmneziaWG obfuscation values support.
+//! - `uapi`: Adds WireGuard's cross-platform userspace API (UAPI) client.
+//! - `prometheus`: Adds Prometheus metrics exposition from peers' runtime status.
+//! - `netlink`: Adds Linux kernel module's configuration via generic netlink.
+//! - `kubernetes`: Adds Kubernetes `Secret`/`ConfigMap` manifests export.
+//! - `amneziavpn`: Adds Amnezia VPN client config (`vpn://` links) export and import.
+//! - `handshake`: Adds in-memory WireGuard handshake test between server and client configs, and
+//!   handshake packet builder for probing servers.
 //!
 //! # Example
 //!
@@ -30,8 +30,10 @@ use crate::prelude::*;
 #[must_use]
 #[derive(Default)]
 pub struct InterfaceBuilder {
-    address: Ipv4Net,
+    address: Option<Ipv4Net>,
     listen_port: Option<u16>,
+    fwmark: Option<u32>,
+    mtu: Option<u16>,
     private_key: Option<PrivateKey>,
     dns: Vec<String>,
     endpoint: Option<String>,
@@ -51,7 +53,7 @@ impl InterfaceBuilder {
     ///
     /// [Wireguard Docs](https://github.com/pirate/wireguard-docs?tab=readme-ov-file#address)
     pub fn address(mut self, address: Ipv4Net) -> Self {
-        self.address = address;
+        self.address = Some(address);
         self
     }
 
@@ -63,6 +65,22 @@ impl InterfaceBuilder {
         self
     }
 
+    /// Set the firewall mark.
+    ///
+    /// [Wireguard Docs](https://github.com/pirate/wireguard-docs?tab=readme-ov-file#fwmark)
+    pub fn fwmark(mut self, fwmark: u32) -> Self {
+        self.fwmark = Some(fwmark);
+        self
+    }
+
+    /// Set the MTU.
+    ///
+    /// [Wireguard Docs](https://github.com/pirate/wireguard-docs?tab=readme-ov-file#mtu)
+    pub fn mtu(mut self, mtu: u16) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
     /// Set the private key.
     ///
     /// [Wireguard Docs](https://github.com/pirate/wireguard-docs?tab=readme-ov-file#privatekey)
@@ -128,10 +146,17 @@ impl InterfaceBuilder {
     }
 
     /// Creates [`Interface`].
+    ///
+    /// # Note
+    ///
+    /// Missing values are filled with defaults: address becomes `0.0.0.0/0` and private key is
+    /// generated randomly. Use [`InterfaceBuilder::try_build()`] to reject incomplete configs.
     pub fn build(self) -> Interface {
         Interface {
-            address: self.address,
+            address: self.address.unwrap_or_default(),
             listen_port: self.listen_port,
+            fwmark: self.fwmark,
+            mtu: self.mtu,
             private_key: self.private_key.unwrap_or_else(PrivateKey::random),
             dns: self.dns,
 
@@ -142,6 +167,88 @@ impl InterfaceBuilder {
             peers: self.peers,
         }
     }
+
+    /// Creates [`Interface`], checking that config is complete and valid.
+    ///
+    /// Unlike [`InterfaceBuilder::build()`], private key isn't generated randomly.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::NoAddressProvided`] -- address wasn't set.
+    /// - [`WireguardError::NoPrivateKeyProvided`] -- private key wasn't set.
+    /// - [`WireguardError::InvalidListenPort`] -- listen port is `0`.
+    /// - [`WireguardError::DuplicateDns`] -- same DNS server is set twice.
+    /// - [`WireguardError::InvalidAmneziaSetting`] -- AmneziaWG values are invalid
+    ///   (see [`AmneziaSettings::validate()`]).
+    /// - [`WireguardError::AmneziaMismatch`] -- peer's AmneziaWG values, that must match on both
+    ///   ends (see [`AmneziaSettings::shared_values_match()`]), differ from interface's, or
+    ///   peer uses AmneziaWG while interface doesn't. Error contains peer's name or public key.
+    ///   Peers without AmneziaWG values are accepted (see [`Interface::amnezia_warnings()`]).
+    pub fn try_build(self) -> WireguardResult<Interface> {
+        if self.address.is_none() {
+            return Err(WireguardError::NoAddressProvided);
+        }
+
+        if self.private_key.is_none() {
+            return Err(WireguardError::NoPrivateKeyProvided);
+        }
+
+        if self.listen_port == Some(0) {
+            return Err(WireguardError::InvalidListenPort);
+        }
+
+        for (i, dns) in self.dns.iter().enumerate() {
+            if self.dns[..i].contains(dns) {
+                return Err(WireguardError::DuplicateDns(dns.clone()));
+            }
+        }
+
+        #[cfg(feature = "amneziawg")]
+        if let Some(amnezia_settings) = &self.amnezia_settings {
+            amnezia_settings.validate()?;
+        }
+
+        #[cfg(feature = "amneziawg")]
+        for peer in &self.peers {
+            let Some(peer_settings) = &peer.amnezia_settings else {
+                continue;
+            };
+            let matches = self
+                .amnezia_settings
+                .as_ref()
+                .is_some_and(|settings| settings.shared_values_match(peer_settings));
+            if !matches {
+                return Err(WireguardError::AmneziaMismatch(
+                    peer.name
+                        .clone()
+                        .unwrap_or_else(|| peer.public_key().to_string()),
+                ));
+            }
+        }
+
+        Ok(self.build())
+    }
+}
+
+/// Creates builder from existing [`Interface`].
+///
+/// See [`Interface::to_builder()`].
+impl From<Interface> for InterfaceBuilder {
+    fn from(interface: Interface) -> Self {
+        Self {
+            address: Some(interface.address),
+            listen_port: interface.listen_port,
+            fwmark: interface.fwmark,
+            mtu: interface.mtu,
+            private_key: Some(interface.private_key),
+            dns: interface.dns,
+            endpoint: interface.endpoint,
+            peers: interface.peers,
+
+            #[cfg(feature = "amneziawg")]
+            amnezia_settings: interface.amnezia_settings,
+        }
+    }
 }
 
 /// Builder, that used for creating [`Peer`]s.
@@ -155,6 +262,7 @@ impl InterfaceBuilder {
 /// let client_private_key = PrivateKey::random();
 ///
 /// let peer = PeerBuilder::new()
+///     .name("Client".to_string())
 ///     .endpoint("public.client.example.com".to_string())
 ///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
 ///     .private_key(client_private_key.clone())
@@ -163,6 +271,7 @@ impl InterfaceBuilder {
 ///     //  .public_key(client_public_key)
 ///     .build();
 ///
+/// assert_eq!(peer.name, Some("Client".to_string()));
 /// assert_eq!(peer.endpoint, Some("public.client.example.com".to_string()));
 /// assert_eq!(peer.allowed_ips, vec!["10.0.0.2/32".parse().unwrap()]);
 /// assert_eq!(peer.key, Either::Left(client_private_key));
@@ -170,6 +279,9 @@ impl InterfaceBuilder {
 #[must_use]
 #[derive(Default)]
 pub struct PeerBuilder {
+    name: Option<String>,
+    description: Option<String>,
+    comments: Vec<String>,
     endpoint: Option<String>,
     allowed_ips: Vec<Ipv4Net>,
     key: Option<Either<PrivateKey, PublicKey>>,
@@ -186,6 +298,38 @@ impl PeerBuilder {
         PeerBuilder::default()
     }
 
+    /// Sets name.
+    ///
+    /// In peer's config this sets `# Name = ...`.
+    pub fn name(mut self, name: String) -> PeerBuilder {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets description.
+    ///
+    /// In peer's config this sets `# Description = ...`.
+    pub fn description(mut self, description: String) -> PeerBuilder {
+        self.description = Some(description);
+        self
+    }
+
+    /// Sets comment lines array.
+    ///
+    /// In peer's config each line is exported as `# ...`.
+    pub fn set_comments(mut self, comments: Vec<String>) -> PeerBuilder {
+        self.comments = comments;
+        self
+    }
+
+    /// Adds comment line.
+    ///
+    /// In peer's config it is exported as `# ...`.
+    pub fn add_comment(mut self, comment: String) -> PeerBuilder {
+        self.comments.push(comment);
+        self
+    }
+
     /// Sets endpoint.
     ///
     /// [Wireguard Docs](https://github.com/pirate/wireguard-docs?tab=readme-ov-file#endpoint)
@@ -263,6 +407,9 @@ impl PeerBuilder {
             .unwrap_or_else(|| Either::Left(PrivateKey::random()));
 
         Peer {
+            name: self.name,
+            description: self.description,
+            comments: self.comments,
             endpoint: self.endpoint,
             allowed_ips: self.allowed_ips,
             key,
@@ -273,4 +420,45 @@ impl PeerBuilder {
             amnezia_settings: self.amnezia_settings,
         }
     }
+
+    /// Creates [`Peer`], checking that config is complete and valid.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::NoAllowedIPs`] -- allowed IPs array is empty.
+    /// - [`WireguardError::InvalidAmneziaSetting`] -- AmneziaWG values are invalid
+    ///   (see [`AmneziaSettings::validate()`]).
+    pub fn try_build(self) -> WireguardResult<Peer> {
+        if self.allowed_ips.is_empty() {
+            return Err(WireguardError::NoAllowedIPs);
+        }
+
+        #[cfg(feature = "amneziawg")]
+        if let Some(amnezia_settings) = &self.amnezia_settings {
+            amnezia_settings.validate()?;
+        }
+
+        Ok(self.build())
+    }
+}
+
+/// Creates builder from existing [`Peer`].
+///
+/// See [`Peer::to_builder()`].
+impl From<Peer> for PeerBuilder {
+    fn from(peer: Peer) -> Self {
+        Self {
+            name: peer.name,
+            description: peer.description,
+            comments: peer.comments,
+            endpoint: peer.endpoint,
+            allowed_ips: peer.allowed_ips,
+            key: Some(peer.key),
+            preshared_key: peer.preshared_key,
+            persistent_keepalive: peer.persistent_keepalive,
+
+            #[cfg(feature = "amneziawg")]
+            amnezia_settings: peer.amnezia_settings,
+        }
+    }
 }
@@ -0,0 +1,474 @@
+use ipnet::Ipv4Net;
+
+use std::fmt;
+
+use crate::prelude::*;
+
+/// Changed field of [`Interface`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum InterfaceChange {
+    Address {
+        old: Ipv4Net,
+        new: Ipv4Net,
+    },
+    ListenPort {
+        old: Option<u16>,
+        new: Option<u16>,
+    },
+    FwMark {
+        old: Option<u32>,
+        new: Option<u32>,
+    },
+    Mtu {
+        old: Option<u16>,
+        new: Option<u16>,
+    },
+    /// Private key was rotated.
+    PrivateKey,
+    Dns {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    Endpoint {
+        old: Option<String>,
+        new: Option<String>,
+    },
+
+    #[cfg(feature = "amneziawg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "amneziawg")))]
+    AmneziaSettings {
+        old: Option<Box<AmneziaSettings>>,
+        new: Option<Box<AmneziaSettings>>,
+    },
+}
+
+/// Changed field of [`Peer`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum PeerChange {
+    Name {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Description {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// Comment lines were added or removed.
+    Comments {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    AllowedIPs {
+        added: Vec<Ipv4Net>,
+        removed: Vec<Ipv4Net>,
+    },
+    Endpoint {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// Preshared key was added, removed or rotated.
+    PresharedKey {
+        old: Option<PresharedKey>,
+        new: Option<PresharedKey>,
+    },
+    PersistentKeepalive {
+        old: Option<u32>,
+        new: Option<u32>,
+    },
+
+    #[cfg(feature = "amneziawg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "amneziawg")))]
+    AmneziaSettings {
+        old: Option<Box<AmneziaSettings>>,
+        new: Option<Box<AmneziaSettings>>,
+    },
+}
+
+/// Changes of single [`Peer`], that exists in both interfaces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerDiff {
+    /// Peer's public key.
+    pub public_key: PublicKey,
+
+    /// Peer's name (in new interface).
+    pub name: Option<String>,
+
+    /// Changed fields.
+    pub changes: Vec<PeerChange>,
+}
+
+/// Difference between two [`Interface`]s.
+///
+/// Peers are matched by [`PublicKey`]. Use [`Interface::diff()`] for creating it, and
+/// [`fmt::Display`] for human-readable review output.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let old = InterfaceBuilder::new()
+///     .address("10.0.0.1/24".parse().unwrap())
+///     .build();
+///
+/// let peer = PeerBuilder::new()
+///     .name("Laptop".to_string())
+///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+///     .build();
+/// let new = old.to_builder().listen_port(51820).add_peer(peer).build();
+///
+/// let diff = old.diff(&new);
+///
+/// assert_eq!(
+///     diff.changes,
+///     vec![InterfaceChange::ListenPort { old: None, new: Some(51820) }]
+/// );
+/// assert_eq!(diff.added_peers.len(), 1);
+/// println!("{diff}");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceDiff {
+    /// Changed interface's fields.
+    pub changes: Vec<InterfaceChange>,
+
+    /// Peers, that exist only in new interface.
+    pub added_peers: Vec<Peer>,
+
+    /// Peers, that exist only in old interface.
+    pub removed_peers: Vec<Peer>,
+
+    /// Peers, that exist in both interfaces, but have different fields.
+    pub modified_peers: Vec<PeerDiff>,
+}
+
+impl InterfaceDiff {
+    /// Returns `true` if interfaces are the same.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.added_peers.is_empty()
+            && self.removed_peers.is_empty()
+            && self.modified_peers.is_empty()
+    }
+}
+
+/// Get values, that are in `new` but not in `old`, and values, that are in `old` but not in `new`.
+fn diff_lists<T: Clone + PartialEq>(old: &[T], new: &[T]) -> (Vec<T>, Vec<T>) {
+    let added = new.iter().filter(|v| !old.contains(v)).cloned().collect();
+    let removed = old.iter().filter(|v| !new.contains(v)).cloned().collect();
+
+    (added, removed)
+}
+
+impl Peer {
+    /// Get changed fields between this (old) peer and `new` peer.
+    ///
+    /// Peers' keys aren't compared, see [`Interface::diff()`].
+    #[must_use]
+    pub fn diff(&self, new: &Peer) -> Vec<PeerChange> {
+        let mut changes = Vec::new();
+
+        if self.name != new.name {
+            changes.push(PeerChange::Name {
+                old: self.name.clone(),
+                new: new.name.clone(),
+            });
+        }
+        if self.description != new.description {
+            changes.push(PeerChange::Description {
+                old: self.description.clone(),
+                new: new.description.clone(),
+            });
+        }
+
+        let (added, removed) = diff_lists(&self.comments, &new.comments);
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(PeerChange::Comments { added, removed });
+        }
+
+        let (added, removed) = diff_lists(&self.allowed_ips, &new.allowed_ips);
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(PeerChange::AllowedIPs { added, removed });
+        }
+
+        if self.endpoint != new.endpoint {
+            changes.push(PeerChange::Endpoint {
+                old: self.endpoint.clone(),
+                new: new.endpoint.clone(),
+            });
+        }
+        if self.preshared_key != new.preshared_key {
+            changes.push(PeerChange::PresharedKey {
+                old: self.preshared_key.clone(),
+                new: new.preshared_key.clone(),
+            });
+        }
+        if self.persistent_keepalive != new.persistent_keepalive {
+            changes.push(PeerChange::PersistentKeepalive {
+                old: self.persistent_keepalive,
+                new: new.persistent_keepalive,
+            });
+        }
+
+        #[cfg(feature = "amneziawg")]
+        if self.amnezia_settings != new.amnezia_settings {
+            changes.push(PeerChange::AmneziaSettings {
+                old: self.amnezia_settings.clone().map(Box::new),
+                new: new.amnezia_settings.clone().map(Box::new),
+            });
+        }
+
+        changes
+    }
+}
+
+impl Interface {
+    /// Get difference between this (old) interface and `new` interface.
+    ///
+    /// Peers are matched by [`PublicKey`]: peer with changed key is reported as removed and added.
+    pub fn diff(&self, new: &Interface) -> InterfaceDiff {
+        let mut diff = InterfaceDiff::default();
+
+        if self.address != new.address {
+            diff.changes.push(InterfaceChange::Address {
+                old: self.address,
+                new: new.address,
+            });
+        }
+        if self.listen_port != new.listen_port {
+            diff.changes.push(InterfaceChange::ListenPort {
+                old: self.listen_port,
+                new: new.listen_port,
+            });
+        }
+        if self.fwmark != new.fwmark {
+            diff.changes.push(InterfaceChange::FwMark {
+                old: self.fwmark,
+                new: new.fwmark,
+            });
+        }
+        if self.mtu != new.mtu {
+            diff.changes.push(InterfaceChange::Mtu {
+                old: self.mtu,
+                new: new.mtu,
+            });
+        }
+        if self.private_key != new.private_key {
+            diff.changes.push(InterfaceChange::PrivateKey);
+        }
+
+        let (added, removed) = diff_lists(&self.dns, &new.dns);
+        if !added.is_empty() || !removed.is_empty() {
+            diff.changes.push(InterfaceChange::Dns { added, removed });
+        }
+
+        if self.endpoint != new.endpoint {
+            diff.changes.push(InterfaceChange::Endpoint {
+                old: self.endpoint.clone(),
+                new: new.endpoint.clone(),
+            });
+        }
+
+        #[cfg(feature = "amneziawg")]
+        if self.amnezia_settings != new.amnezia_settings {
+            diff.changes.push(InterfaceChange::AmneziaSettings {
+                old: self.amnezia_settings.clone().map(Box::new),
+                new: new.amnezia_settings.clone().map(Box::new),
+            });
+        }
+
+        let old_keys: Vec<PublicKey> = self.peers.iter().map(Peer::public_key).collect();
+        let new_keys: Vec<PublicKey> = new.peers.iter().map(Peer::public_key).collect();
+
+        for (old_peer, public_key) in self.peers.iter().zip(&old_keys) {
+            let Some(index) = new_keys.iter().position(|key| key == public_key) else {
+                diff.removed_peers.push(old_peer.clone());
+                continue;
+            };
+
+            let new_peer = &new.peers[index];
+            let changes = old_peer.diff(new_peer);
+            if !changes.is_empty() {
+                diff.modified_peers.push(PeerDiff {
+                    public_key: public_key.clone(),
+                    name: new_peer.name.clone(),
+                    changes,
+                });
+            }
+        }
+
+        for (new_peer, public_key) in new.peers.iter().zip(&new_keys) {
+            if !old_keys.contains(public_key) {
+                diff.added_peers.push(new_peer.clone());
+            }
+        }
+
+        diff
+    }
+}
+
+/// Formats optional value, using `(none)` for [`None`].
+struct OptionDisplay<'a, T>(Option<&'a T>);
+
+impl<T: fmt::Display> fmt::Display for OptionDisplay<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(f, "{value}"),
+            None => write!(f, "(none)"),
+        }
+    }
+}
+
+/// Formats added and removed values as `+a +b -c`.
+fn write_lists<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    added: &[T],
+    removed: &[T],
+) -> fmt::Result {
+    let values = added
+        .iter()
+        .map(|value| format!("+{value}"))
+        .chain(removed.iter().map(|value| format!("-{value}")))
+        .collect::<Vec<String>>();
+
+    write!(f, "{}", values.join(" "))
+}
+
+fn write_change<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    old: Option<&T>,
+    new: Option<&T>,
+) -> fmt::Result {
+    write!(
+        f,
+        "{name}: {} -> {}",
+        OptionDisplay(old),
+        OptionDisplay(new)
+    )
+}
+
+impl fmt::Display for InterfaceChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterfaceChange::Address { old, new } => write!(f, "Address: {old} -> {new}"),
+            InterfaceChange::ListenPort { old, new } => {
+                write_change(f, "ListenPort", old.as_ref(), new.as_ref())
+            }
+            InterfaceChange::FwMark { old, new } => {
+                write_change(f, "FwMark", old.as_ref(), new.as_ref())
+            }
+            InterfaceChange::Mtu { old, new } => write_change(f, "MTU", old.as_ref(), new.as_ref()),
+            InterfaceChange::PrivateKey => write!(f, "PrivateKey: rotated"),
+            InterfaceChange::Dns { added, removed } => {
+                write!(f, "DNS: ")?;
+                write_lists(f, added, removed)
+            }
+            InterfaceChange::Endpoint { old, new } => {
+                write_change(f, "Endpoint", old.as_ref(), new.as_ref())
+            }
+
+            #[cfg(feature = "amneziawg")]
+            InterfaceChange::AmneziaSettings { old, new } => {
+                write_amnezia_change(f, old.as_deref(), new.as_deref())
+            }
+        }
+    }
+}
+
+impl fmt::Display for PeerChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerChange::Name { old, new } => write_change(f, "Name", old.as_ref(), new.as_ref()),
+            PeerChange::Description { old, new } => {
+                write_change(f, "Description", old.as_ref(), new.as_ref())
+            }
+            PeerChange::Comments { added, removed } => {
+                let quote = |comments: &[String]| {
+                    comments
+                        .iter()
+                        .map(|comment| format!("{comment:?}"))
+                        .collect::<Vec<String>>()
+                };
+
+                write!(f, "Comments: ")?;
+                write_lists(f, &quote(added), &quote(removed))
+            }
+            PeerChange::AllowedIPs { added, removed } => {
+                write!(f, "AllowedIPs: ")?;
+                write_lists(f, added, removed)
+            }
+            PeerChange::Endpoint { old, new } => {
+                write_change(f, "Endpoint", old.as_ref(), new.as_ref())
+            }
+            PeerChange::PresharedKey { old, new } => match (old, new) {
+                (None, _) => write!(f, "PresharedKey: added"),
+                (_, None) => write!(f, "PresharedKey: removed"),
+                _ => write!(f, "PresharedKey: rotated"),
+            },
+            PeerChange::PersistentKeepalive { old, new } => {
+                write_change(f, "PersistentKeepalive", old.as_ref(), new.as_ref())
+            }
+
+            #[cfg(feature = "amneziawg")]
+            PeerChange::AmneziaSettings { old, new } => {
+                write_amnezia_change(f, old.as_deref(), new.as_deref())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "amneziawg")]
+fn write_amnezia_change(
+    f: &mut fmt::Formatter<'_>,
+    old: Option<&AmneziaSettings>,
+    new: Option<&AmneziaSettings>,
+) -> fmt::Result {
+    match (old, new) {
+        (None, _) => write!(f, "AmneziaWG: enabled"),
+        (_, None) => write!(f, "AmneziaWG: disabled"),
+        _ => write!(f, "AmneziaWG: changed"),
+    }
+}
+
+fn write_peer_title(
+    f: &mut fmt::Formatter<'_>,
+    prefix: char,
+    public_key: &PublicKey,
+    name: Option<&String>,
+) -> fmt::Result {
+    write!(f, "{prefix} [Peer] {public_key}")?;
+    if let Some(name) = name {
+        write!(f, " ({name})")?;
+    }
+
+    writeln!(f)
+}
+
+/// Implements [`fmt::Display`] for reviewing changes.
+///
+/// Changed fields are prefixed with `~`, added peers with `+` and removed peers with `-`.
+/// Keys' values are never printed, except peers' public keys.
+impl fmt::Display for InterfaceDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "~ {change}")?;
+        }
+
+        for peer in &self.added_peers {
+            write_peer_title(f, '+', &peer.public_key(), peer.name.as_ref())?;
+        }
+        for peer in &self.removed_peers {
+            write_peer_title(f, '-', &peer.public_key(), peer.name.as_ref())?;
+        }
+        for peer in &self.modified_peers {
+            write_peer_title(f, '~', &peer.public_key, peer.name.as_ref())?;
+
+            for change in &peer.changes {
+                writeln!(f, "    {change}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,681 @@
+use ipnet::Ipv4Net;
+
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use super::status::{parse_fwmark, parse_value};
+use crate::prelude::*;
+
+/// Kind of [`Line`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    /// Empty (or whitespace-only) line.
+    Blank,
+
+    /// `# ...` line. Contains text after `#`, without surrounding whitespace.
+    Comment(String),
+
+    /// `Key = Value` line. Inline comments (`Key = Value # ...`) are stripped from value.
+    Entry { key: String, value: String },
+}
+
+/// Single line of [`ConfigDocument`].
+///
+/// Parsed lines remember their source text, so untouched lines are written back as is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    kind: LineKind,
+    raw: Option<String>,
+}
+
+impl Line {
+    /// Creates blank line.
+    #[must_use]
+    pub fn blank() -> Line {
+        Line {
+            kind: LineKind::Blank,
+            raw: None,
+        }
+    }
+
+    /// Creates `# {text}` line.
+    #[must_use]
+    pub fn comment(text: String) -> Line {
+        Line {
+            kind: LineKind::Comment(text),
+            raw: None,
+        }
+    }
+
+    /// Creates `{key} = {value}` line.
+    #[must_use]
+    pub fn entry(key: String, value: String) -> Line {
+        Line {
+            kind: LineKind::Entry { key, value },
+            raw: None,
+        }
+    }
+
+    /// Get line's kind.
+    #[must_use]
+    pub fn kind(&self) -> &LineKind {
+        &self.kind
+    }
+
+    /// Get entry's key, if line is entry.
+    #[must_use]
+    pub fn key(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::Entry { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Get entry's value, if line is entry.
+    #[must_use]
+    pub fn value(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::Entry { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get comment's text, if line is comment.
+    #[must_use]
+    pub fn comment_text(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::Comment(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Sets entry's value. Does nothing if line isn't entry.
+    pub fn set_value(&mut self, new_value: String) {
+        if let LineKind::Entry { value, .. } = &mut self.kind {
+            *value = new_value;
+            self.raw = None;
+        }
+    }
+
+    fn is_key(&self, key: &str) -> bool {
+        self.key().is_some_and(|k| k.eq_ignore_ascii_case(key))
+    }
+
+    fn parse(raw: &str, line_number: usize) -> WireguardResult<Line> {
+        let text = raw.trim();
+
+        let kind = if text.is_empty() {
+            LineKind::Blank
+        } else if let Some(comment) = text.strip_prefix('#') {
+            LineKind::Comment(comment.trim().to_string())
+        } else {
+            let (key, value) = text
+                .split_once('=')
+                .ok_or(WireguardError::InvalidLine(line_number))?;
+            let value = value.split_once('#').map_or(value, |(value, _)| value);
+
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(WireguardError::InvalidLine(line_number));
+            }
+
+            LineKind::Entry {
+                key: key.to_string(),
+                value: value.trim().to_string(),
+            }
+        };
+
+        Ok(Line {
+            kind,
+            raw: Some(raw.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(raw) = &self.raw {
+            return write!(f, "{raw}");
+        }
+
+        match &self.kind {
+            LineKind::Blank => writeln!(f),
+            LineKind::Comment(text) => writeln!(f, "# {text}"),
+            LineKind::Entry { key, value } => writeln!(f, "{key} = {value}"),
+        }
+    }
+}
+
+/// Section of [`ConfigDocument`] (`[Interface]`, `[Peer]`, etc).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    name: String,
+    raw: Option<String>,
+    lines: Vec<Line>,
+}
+
+impl Section {
+    /// Creates empty section.
+    #[must_use]
+    pub fn new(name: String) -> Section {
+        Section {
+            name,
+            raw: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Get section's name (without brackets).
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get all lines of section (after `[...]` header).
+    #[must_use]
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Get mutable lines array.
+    pub fn lines_mut(&mut self) -> &mut Vec<Line> {
+        &mut self.lines
+    }
+
+    /// Get value of first entry with `key`. Keys are case-insensitive.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .find(|line| line.is_key(key))
+            .and_then(Line::value)
+    }
+
+    /// Get values of all entries with `key`. Keys are case-insensitive.
+    #[must_use]
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|line| line.is_key(key))
+            .filter_map(Line::value)
+            .collect()
+    }
+
+    /// Get text of all comments.
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(Line::comment_text)
+    }
+
+    /// Sets value of entry with `key`.
+    ///
+    /// First entry with `key` is updated in place and other entries with `key` are removed.
+    /// If there's no such entry, it's added (see [`Section::add()`]).
+    pub fn set(&mut self, key: &str, value: String) {
+        let Some(index) = self.lines.iter().position(|line| line.is_key(key)) else {
+            self.add(key.to_string(), value);
+            return;
+        };
+
+        self.lines[index].set_value(value);
+
+        let mut current = 0;
+        self.lines.retain(|line| {
+            let keep = current <= index || !line.is_key(key);
+            current += 1;
+
+            keep
+        });
+    }
+
+    /// Adds entry after last non-blank line of section.
+    pub fn add(&mut self, key: String, value: String) {
+        self.insert_line(Line::entry(key, value));
+    }
+
+    /// Adds comment after last non-blank line of section.
+    pub fn add_comment(&mut self, text: String) {
+        self.insert_line(Line::comment(text));
+    }
+
+    /// Removes all entries with `key`. Returns `true` if anything was removed.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.lines.len();
+        self.lines.retain(|line| !line.is_key(key));
+
+        self.lines.len() != len
+    }
+
+    fn insert_line(&mut self, line: Line) {
+        let index = self
+            .lines
+            .iter()
+            .rposition(|line| *line.kind() != LineKind::Blank)
+            .map_or(0, |index| index + 1);
+
+        self.lines.insert(index, line);
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.raw {
+            Some(raw) => write!(f, "{raw}")?,
+            None => writeln!(f, "[{}]", self.name)?,
+        }
+
+        write_lines(f, &self.lines, self.raw.as_deref())
+    }
+}
+
+/// Lossless wg-quick config document.
+///
+/// Unlike [`Interface`], it keeps comments, blank lines, unknown keys and keys ordering, so
+/// untouched parts of config are written back byte-identical.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let config = "\
+/// ## managed by hand
+/// [Interface]
+/// Address = 10.0.0.1/24
+/// PrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+/// ListenPort = 51820 # default port
+/// PostUp = iptables -A FORWARD -i %i -j ACCEPT
+/// ";
+///
+/// let mut document: ConfigDocument = config.parse().unwrap();
+/// assert_eq!(document.to_string(), config);
+///
+/// document.section_mut("Interface").unwrap().set("ListenPort", "51821".to_string());
+/// assert!(document.to_string().contains("ListenPort = 51821\nPostUp"));
+///
+/// let interface = Interface::try_from(&document).unwrap();
+/// assert_eq!(interface.listen_port, Some(51821));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigDocument {
+    preamble: Vec<Line>,
+    sections: Vec<Section>,
+}
+
+impl ConfigDocument {
+    /// Creates empty document.
+    #[must_use]
+    pub fn new() -> ConfigDocument {
+        ConfigDocument::default()
+    }
+
+    /// Parses wg-quick config.
+    ///
+    /// Like in wg-quick, comments after section's header (`[Peer] # laptop`) are allowed.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidLine`] -- line isn't blank, comment, `[Section]` or
+    ///   `Key = Value`; or there's entry before first section.
+    pub fn parse(text: &str) -> WireguardResult<ConfigDocument> {
+        let mut document = ConfigDocument::new();
+
+        for (index, raw) in text.split_inclusive('\n').enumerate() {
+            let line_number = index + 1;
+            // like wg-quick, comments after section's header are ignored (but kept in raw line)
+            let header = raw.split_once('#').map_or(raw, |(header, _)| header).trim();
+
+            if let Some(name) = header.strip_prefix('[') {
+                let name = name
+                    .strip_suffix(']')
+                    .ok_or(WireguardError::InvalidLine(line_number))?;
+
+                document.sections.push(Section {
+                    name: name.trim().to_string(),
+                    raw: Some(raw.to_string()),
+                    lines: Vec::new(),
+                });
+
+                continue;
+            }
+
+            let line = Line::parse(raw, line_number)?;
+
+            match document.sections.last_mut() {
+                Some(section) => section.lines.push(line),
+                None if line.key().is_none() => document.preamble.push(line),
+                None => return Err(WireguardError::InvalidLine(line_number)),
+            }
+        }
+
+        Ok(document)
+    }
+
+    /// Get lines before first section (only blank lines and comments).
+    #[must_use]
+    pub fn preamble(&self) -> &[Line] {
+        &self.preamble
+    }
+
+    /// Get mutable lines before first section.
+    pub fn preamble_mut(&mut self) -> &mut Vec<Line> {
+        &mut self.preamble
+    }
+
+    /// Get all sections.
+    #[must_use]
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Get mutable sections array.
+    pub fn sections_mut(&mut self) -> &mut Vec<Section> {
+        &mut self.sections
+    }
+
+    /// Get first section named `name`. Names are case-insensitive.
+    #[must_use]
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Get first mutable section named `name`. Names are case-insensitive.
+    pub fn section_mut(&mut self, name: &str) -> Option<&mut Section> {
+        self.sections
+            .iter_mut()
+            .find(|section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Get all sections named `name`. Names are case-insensitive.
+    pub fn sections_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Section> {
+        self.sections
+            .iter()
+            .filter(move |section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Adds section to the end of document.
+    ///
+    /// If last section doesn't end with blank line, it's added before new section.
+    pub fn add_section(&mut self, section: Section) {
+        if let Some(last) = self.sections.last_mut() {
+            if last
+                .lines
+                .last()
+                .is_none_or(|line| *line.kind() != LineKind::Blank)
+            {
+                last.lines.push(Line::blank());
+            }
+        }
+
+        self.sections.push(section);
+    }
+}
+
+impl FromStr for ConfigDocument {
+    type Err = WireguardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ConfigDocument::parse(s)
+    }
+}
+
+impl fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_lines(f, &self.preamble, None)?;
+
+        let mut previous = self.preamble.last().and_then(|line| line.raw.as_deref());
+        for section in &self.sections {
+            if previous.is_some_and(|raw| !raw.ends_with('\n')) {
+                writeln!(f)?;
+            }
+            write!(f, "{section}")?;
+
+            previous = section
+                .lines
+                .last()
+                .map_or(section.raw.as_deref(), |line| line.raw.as_deref());
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes lines, adding line break after source lines, which don't have it (last line of file).
+fn write_lines(f: &mut fmt::Formatter<'_>, lines: &[Line], previous: Option<&str>) -> fmt::Result {
+    let mut previous = previous;
+
+    for line in lines {
+        if previous.is_some_and(|raw| !raw.ends_with('\n')) {
+            writeln!(f)?;
+        }
+        write!(f, "{line}")?;
+
+        previous = line.raw.as_deref();
+    }
+
+    Ok(())
+}
+
+/// Parses comma-separated IPv4 networks. Bare addresses are treated as `/32`.
+///
+/// IPv6 networks are skipped, because they aren't supported yet.
+pub(super) fn parse_networks(values: &[&str], key: &str) -> WireguardResult<Vec<Ipv4Net>> {
+    let mut networks = Vec::new();
+
+    for value in values.iter().flat_map(|value| value.split(',')) {
+        let value = value.trim();
+        if value.is_empty() || value.contains(':') {
+            continue;
+        }
+
+        let network = value
+            .parse::<Ipv4Net>()
+            .or_else(|_| value.parse::<Ipv4Addr>().map(Ipv4Net::from))
+            .map_err(|_| WireguardError::InvalidValue(key.to_string()))?;
+
+        networks.push(network);
+    }
+
+    Ok(networks)
+}
+
+pub(super) fn parse_number<T: FromStr>(section: &Section, key: &str) -> WireguardResult<Option<T>> {
+    section
+        .get(key)
+        .map(|value| parse_value(value, key))
+        .transpose()
+}
+
+/// Get value of `comment`, if it's `key = value` (e.g. `Name = ...`).
+pub(super) fn metadata_field<'a>(comment: &'a str, key: &str) -> Option<&'a str> {
+    let (k, v) = comment.split_once('=')?;
+    (k.trim() == key).then(|| v.trim())
+}
+
+/// Get `Key = Value` pairs from section's comments (`# Name = ...`).
+fn comment_field<'a>(section: &'a Section, key: &str) -> Option<&'a str> {
+    section
+        .comments()
+        .find_map(|comment| metadata_field(comment, key))
+}
+
+impl TryFrom<&Section> for Peer {
+    type Error = WireguardError;
+
+    fn try_from(section: &Section) -> Result<Self, Self::Error> {
+        let public_key = section
+            .get("PublicKey")
+            .ok_or(WireguardError::NoPublicKeyProvided)?;
+        let public_key = PublicKey::try_from(public_key.to_string())?;
+
+        let allowed_ips = parse_networks(&section.get_all("AllowedIPs"), "AllowedIPs")?;
+
+        let mut builder = PeerBuilder::new()
+            .public_key(public_key)
+            .set_allowed_ips(allowed_ips);
+
+        // only `# Name = ...` and `# Description = ...` right after `[Peer]` (where `Peer`'s
+        // `Display` writes them) are metadata, other comments are kept as is
+        let mut leading = section
+            .lines()
+            .iter()
+            .map_while(Line::comment_text)
+            .peekable();
+        let mut metadata = |key: &str| {
+            leading
+                .next_if(|comment| metadata_field(comment, key).is_some())
+                .and_then(|comment| metadata_field(comment, key))
+        };
+        let name = metadata("Name");
+        let description = metadata("Description");
+
+        let metadata_count = usize::from(name.is_some()) + usize::from(description.is_some());
+        for comment in section.comments().skip(metadata_count) {
+            builder = builder.add_comment(comment.to_string());
+        }
+        if let Some(name) = name {
+            builder = builder.name(name.to_string());
+        }
+        if let Some(description) = description {
+            builder = builder.description(description.to_string());
+        }
+
+        if let Some(endpoint) = section.get("Endpoint") {
+            builder = builder.endpoint(endpoint.to_string());
+        }
+        if let Some(preshared_key) = section.get("PresharedKey") {
+            builder = builder.preshared_key(PresharedKey::try_from(preshared_key.to_string())?);
+        }
+        if section.get("PersistentKeepalive") != Some("off") {
+            if let Some(persistent_keepalive) = parse_number(section, "PersistentKeepalive")? {
+                builder = builder.persistent_keepalive(persistent_keepalive);
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// Converts document to [`Interface`].
+///
+/// First `[Interface]` section is used. Unknown keys and sections are ignored.
+///
+/// # Note
+///
+/// IPv6 networks in `Address` and `AllowedIPs` are skipped, because they aren't supported yet.
+/// If `Address` has multiple IPv4 networks, the first one is used.
+///
+/// # Errors
+///
+/// - [`WireguardError::MissingSection`] -- there's no `[Interface]` section.
+/// - [`WireguardError::NoAddressProvided`] -- there's no IPv4 `Address`.
+/// - [`WireguardError::NoPrivateKeyProvided`] -- there's no `PrivateKey`.
+/// - [`WireguardError::NoPublicKeyProvided`] -- peer doesn't have `PublicKey`.
+/// - [`WireguardError::InvalidValue`] -- value can't be parsed.
+/// - [`WireguardError::InvalidPrivateKey`], [`WireguardError::InvalidPublicKey`],
+///   [`WireguardError::InvalidPresharedKey`] -- key can't be parsed.
+/// - [`WireguardError::InvalidAmneziaSetting`] -- AmneziaWG value (`Jc`, `S1`, `H1`, etc.) is
+///   missing, can't be parsed or is invalid (see [`AmneziaSettings::validate()`]). Requires
+///   `amneziawg` feature, otherwise these keys are ignored.
+impl TryFrom<&ConfigDocument> for Interface {
+    type Error = WireguardError;
+
+    fn try_from(document: &ConfigDocument) -> Result<Self, Self::Error> {
+        let section = document
+            .section("Interface")
+            .ok_or(WireguardError::MissingSection("Interface".to_string()))?;
+
+        let address = *parse_networks(&section.get_all("Address"), "Address")?
+            .first()
+            .ok_or(WireguardError::NoAddressProvided)?;
+
+        let private_key = section
+            .get("PrivateKey")
+            .ok_or(WireguardError::NoPrivateKeyProvided)?;
+        let private_key = PrivateKey::try_from(private_key.to_string())?;
+
+        let mut builder = InterfaceBuilder::new()
+            .address(address)
+            .private_key(private_key)
+            .set_dns(
+                section
+                    .get_all("DNS")
+                    .iter()
+                    .flat_map(|value| value.split(','))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(ToString::to_string)
+                    .collect(),
+            );
+
+        if let Some(endpoint) = comment_field(section, "Name") {
+            builder = builder.endpoint(endpoint.to_string());
+        }
+        if let Some(listen_port) = parse_number(section, "ListenPort")? {
+            builder = builder.listen_port(listen_port);
+        }
+        if let Some(fwmark) = section.get("FwMark").filter(|&value| value != "off") {
+            let fwmark = parse_fwmark(fwmark, "FwMark")?;
+
+            builder = builder.fwmark(fwmark);
+        }
+        if let Some(mtu) = parse_number(section, "MTU")? {
+            builder = builder.mtu(mtu);
+        }
+        #[cfg(feature = "amneziawg")]
+        if let Some(amnezia_settings) = AmneziaSettings::from_values(|key| section.get(key))? {
+            builder = builder.amnezia_settings(amnezia_settings);
+        }
+
+        for section in document.sections_named("Peer") {
+            builder = builder.add_peer(Peer::try_from(section)?);
+        }
+
+        Ok(builder.build())
+    }
+}
+
+impl TryFrom<ConfigDocument> for Interface {
+    type Error = WireguardError;
+
+    fn try_from(document: ConfigDocument) -> Result<Self, Self::Error> {
+        Interface::try_from(&document)
+    }
+}
+
+/// Converts [`Interface`] to document with the same content, as [`Interface`]'s
+/// [`fmt::Display`].
+///
+/// # Errors
+///
+/// - [`WireguardError::InvalidLine`] -- exported config can't be parsed back (e.g. endpoint
+///   contains line break).
+impl TryFrom<&Interface> for ConfigDocument {
+    type Error = WireguardError;
+
+    fn try_from(interface: &Interface) -> Result<Self, Self::Error> {
+        ConfigDocument::parse(&interface.to_string())
+    }
+}
+
+/// Converts [`Peer`] to section with the same content, as [`Peer`]'s [`fmt::Display`].
+///
+/// # Errors
+///
+/// - [`WireguardError::InvalidLine`] -- exported peer can't be parsed back (e.g. endpoint
+///   contains line break).
+impl TryFrom<&Peer> for Section {
+    type Error = WireguardError;
+
+    fn try_from(peer: &Peer) -> Result<Self, Self::Error> {
+        let document = ConfigDocument::parse(&peer.to_string())?;
+
+        document
+            .sections
+            .into_iter()
+            .next()
+            .ok_or_else(|| WireguardError::MissingSection("Peer".to_string()))
+    }
+}
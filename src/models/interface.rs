@@ -2,7 +2,9 @@ use either::Either;
 use ipnet::Ipv4Net;
 
 use std::fmt;
+use std::str::FromStr;
 
+use super::peer::single_line;
 use crate::prelude::*;
 
 #[must_use]
@@ -10,6 +12,8 @@ use crate::prelude::*;
 pub struct Interface {
     pub address: Ipv4Net,
     pub listen_port: Option<u16>,
+    pub fwmark: Option<u32>,
+    pub mtu: Option<u16>,
     pub private_key: PrivateKey,
     pub dns: Vec<String>,
     pub endpoint: Option<String>,
@@ -24,6 +28,9 @@ pub struct Interface {
 impl Interface {
     pub fn to_peer(&self) -> Peer {
         Peer {
+            name: None,
+            description: None,
+            comments: Vec::new(),
             endpoint: self.endpoint.clone(),
             allowed_ips: vec![self.address],
             key: Either::Left(self.private_key.clone()),
@@ -34,18 +41,47 @@ impl Interface {
             amnezia_settings: self.amnezia_settings.clone(),
         }
     }
+
+    /// Get [`InterfaceBuilder`] with all values of this interface.
+    ///
+    /// Use it for modifying existing interfaces through builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let interface = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .build();
+    ///
+    /// let modified = interface.to_builder().listen_port(51820).build();
+    ///
+    /// assert_eq!(modified.address, interface.address);
+    /// assert_eq!(modified.private_key, interface.private_key);
+    /// assert_eq!(modified.listen_port, Some(51820));
+    /// ```
+    pub fn to_builder(&self) -> InterfaceBuilder {
+        InterfaceBuilder::from(self.clone())
+    }
 }
 
 impl fmt::Display for Interface {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "[Interface]")?;
         if let Some(endpoint) = &self.endpoint {
-            writeln!(f, "# Name = {endpoint}")?;
+            writeln!(f, "# Name = {}", single_line(endpoint))?;
         }
         writeln!(f, "Address = {}", self.address)?;
         if let Some(listen_port) = self.listen_port {
             writeln!(f, "ListenPort = {listen_port}")?;
         }
+        if let Some(fwmark) = self.fwmark {
+            writeln!(f, "FwMark = {fwmark}")?;
+        }
+        if let Some(mtu) = self.mtu {
+            writeln!(f, "MTU = {mtu}")?;
+        }
         writeln!(f, "PrivateKey = {}", self.private_key)?;
         if !self.dns.is_empty() {
             writeln!(f, "DNS = {}", self.dns.join(","))?;
@@ -65,3 +101,36 @@ impl fmt::Display for Interface {
         fmt::Result::Ok(())
     }
 }
+
+/// Parses wg-quick config.
+///
+/// See [`ConfigDocument`] and [`Interface`]'s `TryFrom<&ConfigDocument>` for details.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let interface: Interface = "\
+/// [Interface]
+/// Address = 10.0.0.1/24
+/// PrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+///
+/// [Peer]
+/// ## Name = Laptop
+/// PublicKey = xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=
+/// AllowedIPs = 10.0.0.2/32
+/// "
+/// .parse()
+/// .unwrap();
+///
+/// assert_eq!(interface.address, "10.0.0.1/24".parse().unwrap());
+/// assert_eq!(interface.peers[0].name, Some("Laptop".to_string()));
+/// ```
+impl FromStr for Interface {
+    type Err = WireguardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Interface::try_from(&ConfigDocument::parse(s)?)
+    }
+}
@@ -1,7 +1,22 @@
 mod builders;
+mod diff;
+mod document;
 mod interface;
+mod networkd;
+mod nm_connection;
 mod peer;
+mod routeros;
+mod status;
+mod uci;
+mod wg_set;
 
 pub use builders::*;
+pub use diff::*;
+pub use document::*;
 pub use interface::*;
+pub use networkd::*;
+pub use nm_connection::*;
 pub use peer::*;
+pub use status::*;
+pub use uci::*;
+pub use wg_set::*;
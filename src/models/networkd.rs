@@ -0,0 +1,242 @@
+use std::fmt::Write as _;
+
+use crate::prelude::*;
+
+use super::peer::single_line;
+
+/// Default routing table for default route, same as `wg-quick`'s.
+const DEFAULT_ROUTE_TABLE: u32 = 51820;
+
+/// systemd-networkd config: `.netdev` and `.network` files.
+///
+/// - [systemd.netdev(5)](https://www.freedesktop.org/software/systemd/man/latest/systemd.netdev.html#%5BWireGuard%5D%20Section%20Options)
+/// - [systemd.network(5)](https://www.freedesktop.org/software/systemd/man/latest/systemd.network.html)
+///
+/// # Note
+///
+/// Route is added for every peer's allowed IP, that isn't inside interface's address. Default
+/// route (`0.0.0.0/0`) would also carry traffic to peer's endpoint, looping it, so it's added
+/// to separate routing table with policy routing, like `wg-quick` does: interface's fwmark
+/// (table's number, if it isn't set) is set on WireGuard's own packets, and other packets are
+/// routed through the table (see [`NetworkdConfig::default_route_table()`]). AmneziaWG values
+/// aren't supported by systemd-networkd, so they're ignored.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let interface = InterfaceBuilder::new()
+///     .address("10.0.0.1/24".parse().unwrap())
+///     .listen_port(51820)
+///     .build();
+///
+/// let config = NetworkdConfig::new(&interface, "wg0".to_string())
+///     .private_key_file("/etc/systemd/network/wg0.key".to_string());
+///
+/// // write it to `/etc/systemd/network/wg0.netdev` and `/etc/systemd/network/wg0.network`
+/// let netdev = config.netdev();
+/// let network = config.network();
+///
+/// assert!(netdev.contains("PrivateKeyFile=/etc/systemd/network/wg0.key\n"));
+/// assert!(network.contains("Address=10.0.0.1/24\n"));
+/// ```
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct NetworkdConfig<'a> {
+    interface: &'a Interface,
+    name: String,
+    private_key_file: Option<String>,
+    preshared_key_files: Vec<(PublicKey, String)>,
+    default_route_table: Option<u32>,
+}
+
+impl<'a> NetworkdConfig<'a> {
+    /// Creates config for `interface` named `name` (e.g. `wg0`).
+    pub fn new(interface: &'a Interface, name: String) -> Self {
+        Self {
+            interface,
+            name,
+            private_key_file: None,
+            preshared_key_files: Vec::new(),
+            default_route_table: Some(DEFAULT_ROUTE_TABLE),
+        }
+    }
+
+    /// Sets `PrivateKeyFile=` instead of inline `PrivateKey=`.
+    ///
+    /// File must contain private key and must be readable by `systemd-network` user.
+    pub fn private_key_file(mut self, path: String) -> Self {
+        self.private_key_file = Some(path);
+        self
+    }
+
+    /// Sets `PresharedKeyFile=` instead of inline `PresharedKey=` for peer with `public_key`.
+    pub fn preshared_key_file(mut self, public_key: PublicKey, path: String) -> Self {
+        self.preshared_key_files
+            .retain(|(key, _)| *key != public_key);
+        self.preshared_key_files.push((public_key, path));
+        self
+    }
+
+    /// Sets routing table for default route (`0.0.0.0/0`), `51820` by default.
+    ///
+    /// If it's `None`, default route isn't added, so full tunnel must be set up by hand.
+    pub fn default_route_table(mut self, table: Option<u32>) -> Self {
+        self.default_route_table = table;
+        self
+    }
+
+    /// Get routing table and fwmark for default route, if some peer has it.
+    fn default_route(&self) -> Option<(u32, u32)> {
+        let table = self.default_route_table?;
+        let has_default_route = self
+            .interface
+            .peers
+            .iter()
+            .flat_map(|peer| &peer.allowed_ips)
+            .any(|allowed_ip| allowed_ip.prefix_len() == 0);
+
+        has_default_route.then(|| (table, self.interface.fwmark.unwrap_or(table)))
+    }
+
+    /// Get `.netdev` file.
+    #[must_use]
+    pub fn netdev(&self) -> String {
+        let interface = self.interface;
+        let mut netdev = String::new();
+
+        let _ = writeln!(netdev, "[NetDev]");
+        let _ = writeln!(netdev, "Name={}", self.name);
+        let _ = writeln!(netdev, "Kind=wireguard");
+        if let Some(mtu) = interface.mtu {
+            let _ = writeln!(netdev, "MTUBytes={mtu}");
+        }
+
+        let _ = writeln!(netdev);
+        let _ = writeln!(netdev, "[WireGuard]");
+        match &self.private_key_file {
+            Some(path) => {
+                let _ = writeln!(netdev, "PrivateKeyFile={path}");
+            }
+            None => {
+                let _ = writeln!(netdev, "PrivateKey={}", interface.private_key);
+            }
+        }
+        if let Some(listen_port) = interface.listen_port {
+            let _ = writeln!(netdev, "ListenPort={listen_port}");
+        }
+        let fwmark = self.default_route().map(|(_, fwmark)| fwmark);
+        if let Some(fwmark) = fwmark.or(interface.fwmark) {
+            let _ = writeln!(netdev, "FirewallMark={fwmark}");
+        }
+
+        for peer in &interface.peers {
+            let public_key = peer.public_key();
+
+            let _ = writeln!(netdev);
+            let _ = writeln!(netdev, "[WireGuardPeer]");
+            if let Some(name) = &peer.name {
+                let _ = writeln!(netdev, "# Name = {}", single_line(name));
+            }
+            if let Some(description) = &peer.description {
+                let _ = writeln!(netdev, "# Description = {}", single_line(description));
+            }
+            let _ = writeln!(netdev, "PublicKey={public_key}");
+            let preshared_key_file = self
+                .preshared_key_files
+                .iter()
+                .find(|(key, _)| *key == public_key)
+                .map(|(_, path)| path);
+
+            match (preshared_key_file, &peer.preshared_key) {
+                (Some(path), _) => {
+                    let _ = writeln!(netdev, "PresharedKeyFile={path}");
+                }
+                (None, Some(preshared_key)) => {
+                    let _ = writeln!(netdev, "PresharedKey={preshared_key}");
+                }
+                (None, None) => {}
+            }
+            for allowed_ip in &peer.allowed_ips {
+                let _ = writeln!(netdev, "AllowedIPs={allowed_ip}");
+            }
+            if let Some(endpoint) = &peer.endpoint {
+                let _ = writeln!(netdev, "Endpoint={endpoint}");
+            }
+            if let Some(persistent_keepalive) = peer.persistent_keepalive {
+                let _ = writeln!(netdev, "PersistentKeepalive={persistent_keepalive}");
+            }
+        }
+
+        netdev
+    }
+
+    /// Get `.network` file.
+    #[must_use]
+    pub fn network(&self) -> String {
+        let interface = self.interface;
+        let mut network = String::new();
+
+        let _ = writeln!(network, "[Match]");
+        let _ = writeln!(network, "Name={}", self.name);
+
+        let _ = writeln!(network);
+        let _ = writeln!(network, "[Network]");
+        let _ = writeln!(network, "Address={}", interface.address);
+        for dns in &interface.dns {
+            let _ = writeln!(network, "DNS={dns}");
+        }
+
+        let mut destinations = Vec::new();
+        for allowed_ip in interface.peers.iter().flat_map(|peer| &peer.allowed_ips) {
+            let destination = allowed_ip.trunc();
+            if destination.prefix_len() != 0
+                && !interface.address.trunc().contains(&destination)
+                && !destinations.contains(&destination)
+            {
+                destinations.push(destination);
+            }
+        }
+
+        for destination in destinations {
+            let _ = writeln!(network);
+            let _ = writeln!(network, "[Route]");
+            let _ = writeln!(network, "Destination={destination}");
+        }
+
+        if let Some((table, fwmark)) = self.default_route() {
+            let _ = writeln!(network);
+            let _ = writeln!(network, "[Route]");
+            let _ = writeln!(network, "Destination=0.0.0.0/0");
+            let _ = writeln!(network, "Table={table}");
+
+            // main table is still used, except for its default route
+            let _ = writeln!(network);
+            let _ = writeln!(network, "[RoutingPolicyRule]");
+            let _ = writeln!(network, "Table=main");
+            let _ = writeln!(network, "SuppressPrefixLength=0");
+            let _ = writeln!(network, "Priority=32764");
+
+            // WireGuard's own packets (with fwmark) don't go through tunnel
+            let _ = writeln!(network);
+            let _ = writeln!(network, "[RoutingPolicyRule]");
+            let _ = writeln!(network, "FirewallMark={fwmark}");
+            let _ = writeln!(network, "InvertRule=true");
+            let _ = writeln!(network, "Table={table}");
+            let _ = writeln!(network, "Priority=32765");
+        }
+
+        network
+    }
+}
+
+impl Interface {
+    /// Get systemd-networkd config (`.netdev` and `.network` files) for this interface named
+    /// `name`.
+    ///
+    /// See [`NetworkdConfig`].
+    pub fn to_networkd(&self, name: &str) -> NetworkdConfig<'_> {
+        NetworkdConfig::new(self, name.to_string())
+    }
+}
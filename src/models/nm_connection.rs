@@ -0,0 +1,306 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+
+use super::document::{parse_networks, parse_number};
+use crate::prelude::*;
+
+/// Prefix of peer's section name (`[wireguard-peer.<public-key>]`).
+const PEER_SECTION_PREFIX: &str = "wireguard-peer.";
+
+/// `NM_SETTING_SECRET_FLAG_NOT_REQUIRED`: secret isn't needed.
+const SECRET_FLAG_NOT_REQUIRED: u32 = 4;
+
+/// NetworkManager keyfile (`.nmconnection`).
+///
+/// Use [`fmt::Display`] for exporting and [`Interface::from_nmconnection()`] for importing.
+///
+/// - [nm-settings-keyfile(5)](https://networkmanager.dev/docs/api/latest/nm-settings-keyfile.html)
+/// - [WireGuard settings](https://networkmanager.dev/docs/api/latest/settings-wireguard.html)
+///
+/// # Note
+///
+/// Preshared keys are stored in the keyfile (`preshared-key-flags=0`); peers without preshared
+/// key don't have flags. IPv6 DNS servers are
+/// skipped, because IPv6 isn't supported yet; DNS entries, which aren't IP addresses, are
+/// exported as search domains (`dns-search`).
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let interface = InterfaceBuilder::new()
+///     .address("10.0.0.2/24".parse().unwrap())
+///     .add_dns("10.0.0.1".to_string())
+///     .build();
+///
+/// // write it to `/etc/NetworkManager/system-connections/wg0.nmconnection` (with `0600` mode)
+/// let keyfile = interface.to_nmconnection("wg0").to_string();
+///
+/// assert!(keyfile.starts_with("[connection]\nid=wg0\ntype=wireguard\ninterface-name=wg0\n"));
+///
+/// let imported = Interface::from_nmconnection(&keyfile).unwrap();
+/// assert_eq!(imported.address, interface.address);
+/// assert_eq!(imported.dns, interface.dns);
+/// ```
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct NmConnection<'a> {
+    interface: &'a Interface,
+    id: String,
+    uuid: Option<String>,
+}
+
+impl<'a> NmConnection<'a> {
+    /// Creates keyfile for `interface`. `id` is used as connection's name and interface's name.
+    pub fn new(interface: &'a Interface, id: String) -> Self {
+        Self {
+            interface,
+            id,
+            uuid: None,
+        }
+    }
+
+    /// Sets connection's UUID.
+    ///
+    /// If it isn't set, NetworkManager generates it from keyfile's path.
+    pub fn uuid(mut self, uuid: String) -> Self {
+        self.uuid = Some(uuid);
+        self
+    }
+}
+
+/// Implements [`fmt::Display`] for exporting keyfile.
+impl fmt::Display for NmConnection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let interface = self.interface;
+
+        writeln!(f, "[connection]")?;
+        writeln!(f, "id={}", self.id)?;
+        if let Some(uuid) = &self.uuid {
+            writeln!(f, "uuid={uuid}")?;
+        }
+        writeln!(f, "type=wireguard")?;
+        writeln!(f, "interface-name={}", self.id)?;
+
+        writeln!(f)?;
+        writeln!(f, "[wireguard]")?;
+        writeln!(f, "private-key={}", interface.private_key)?;
+        if let Some(listen_port) = interface.listen_port {
+            writeln!(f, "listen-port={listen_port}")?;
+        }
+        if let Some(fwmark) = interface.fwmark {
+            writeln!(f, "fwmark={fwmark}")?;
+        }
+        if let Some(mtu) = interface.mtu {
+            writeln!(f, "mtu={mtu}")?;
+        }
+
+        for peer in &interface.peers {
+            writeln!(f)?;
+            writeln!(f, "[{PEER_SECTION_PREFIX}{}]", peer.public_key())?;
+            if let Some(endpoint) = &peer.endpoint {
+                writeln!(f, "endpoint={endpoint}")?;
+            }
+            if let Some(preshared_key) = &peer.preshared_key {
+                writeln!(f, "preshared-key={preshared_key}")?;
+                writeln!(f, "preshared-key-flags=0")?;
+            }
+            if let Some(persistent_keepalive) = peer.persistent_keepalive {
+                writeln!(f, "persistent-keepalive={persistent_keepalive}")?;
+            }
+            write_list(f, "allowed-ips", &peer.allowed_ips)?;
+        }
+
+        let dns: Vec<&String> = interface
+            .dns
+            .iter()
+            .filter(|dns| dns.parse::<Ipv4Addr>().is_ok())
+            .collect();
+        let dns_search: Vec<&String> = interface
+            .dns
+            .iter()
+            .filter(|dns| dns.parse::<IpAddr>().is_err())
+            .collect();
+
+        writeln!(f)?;
+        writeln!(f, "[ipv4]")?;
+        writeln!(f, "address1={}", interface.address)?;
+        write_list(f, "dns", &dns)?;
+        write_list(f, "dns-search", &dns_search)?;
+        writeln!(f, "method=manual")?;
+
+        writeln!(f)?;
+        writeln!(f, "[ipv6]")?;
+        writeln!(f, "addr-gen-mode=default")?;
+        writeln!(f, "method=disabled")?;
+
+        Ok(())
+    }
+}
+
+/// Writes NetworkManager's list (`key=a;b;c;`). Empty lists aren't written.
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, key: &str, values: &[T]) -> fmt::Result {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    write!(f, "{key}=")?;
+    for value in values {
+        write!(f, "{value};")?;
+    }
+    writeln!(f)
+}
+
+/// Splits NetworkManager's list (`a;b;c;`).
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn peer_from_section(public_key: &str, section: &Section) -> WireguardResult<Peer> {
+    let public_key = PublicKey::try_from(public_key.to_string())?;
+
+    let allowed_ips: Vec<&str> = section
+        .get_all("allowed-ips")
+        .into_iter()
+        .flat_map(split_list)
+        .collect();
+
+    let mut builder = PeerBuilder::new()
+        .public_key(public_key)
+        .set_allowed_ips(parse_networks(&allowed_ips, "allowed-ips")?);
+
+    if let Some(endpoint) = section.get("endpoint") {
+        builder = builder.endpoint(endpoint.to_string());
+    }
+
+    let preshared_key_flags = parse_number::<u32>(section, "preshared-key-flags")?.unwrap_or(0);
+    if let Some(preshared_key) = section
+        .get("preshared-key")
+        .filter(|_| preshared_key_flags & SECRET_FLAG_NOT_REQUIRED == 0)
+    {
+        builder = builder.preshared_key(PresharedKey::try_from(preshared_key.to_string())?);
+    }
+
+    if let Some(persistent_keepalive) = parse_number(section, "persistent-keepalive")? {
+        if persistent_keepalive != 0 {
+            builder = builder.persistent_keepalive(persistent_keepalive);
+        }
+    }
+
+    Ok(builder.build())
+}
+
+impl Interface {
+    /// Get NetworkManager keyfile for this interface. `id` is used as connection's name and
+    /// interface's name.
+    ///
+    /// See [`NmConnection`].
+    pub fn to_nmconnection(&self, id: &str) -> NmConnection<'_> {
+        NmConnection::new(self, id.to_string())
+    }
+
+    /// Imports interface from NetworkManager keyfile (`.nmconnection`).
+    ///
+    /// Unknown keys and sections are ignored. Preshared keys with "not required" flag are
+    /// ignored too.
+    ///
+    /// # Note
+    ///
+    /// IPv6 addresses and allowed IPs are skipped, because they aren't supported yet. If keyfile
+    /// has multiple IPv4 addresses, the first one is used.
+    ///
+    /// Search domains (`dns-search`) are added to [`Interface::dns`] after DNS servers, like in
+    /// wg-quick's `DNS =`, and they're exported back to `dns-search`.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidLine`] -- keyfile can't be parsed.
+    /// - [`WireguardError::MissingSection`] -- there's no `[wireguard]` section.
+    /// - [`WireguardError::NoPrivateKeyProvided`] -- there's no `private-key`.
+    /// - [`WireguardError::NoAddressProvided`] -- there's no IPv4 address.
+    /// - [`WireguardError::InvalidValue`] -- value can't be parsed.
+    /// - [`WireguardError::InvalidPrivateKey`], [`WireguardError::InvalidPublicKey`],
+    ///   [`WireguardError::InvalidPresharedKey`] -- key can't be parsed.
+    pub fn from_nmconnection(keyfile: &str) -> WireguardResult<Interface> {
+        let document = ConfigDocument::parse(keyfile)?;
+
+        let wireguard = document
+            .section("wireguard")
+            .ok_or(WireguardError::MissingSection("wireguard".to_string()))?;
+
+        let private_key = wireguard
+            .get("private-key")
+            .ok_or(WireguardError::NoPrivateKeyProvided)?;
+
+        let mut builder =
+            InterfaceBuilder::new().private_key(PrivateKey::try_from(private_key.to_string())?);
+
+        if let Some(listen_port) = parse_number(wireguard, "listen-port")? {
+            builder = builder.listen_port(listen_port);
+        }
+        if let Some(fwmark) = parse_number(wireguard, "fwmark")? {
+            if fwmark != 0 {
+                builder = builder.fwmark(fwmark);
+            }
+        }
+        if let Some(mtu) = parse_number(wireguard, "mtu")? {
+            if mtu != 0 {
+                builder = builder.mtu(mtu);
+            }
+        }
+
+        for section in document.sections() {
+            if let Some(public_key) = section.name().strip_prefix(PEER_SECTION_PREFIX) {
+                builder = builder.add_peer(peer_from_section(public_key, section)?);
+            }
+        }
+
+        let ipv4 = document.section("ipv4");
+
+        // `addressN=<address>[,<gateway>]`, ordered by `N`
+        let mut addresses: Vec<(usize, &str)> = ipv4
+            .map(Section::lines)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| {
+                let index = line.key()?.strip_prefix("address")?;
+                let index = if index.is_empty() {
+                    0
+                } else {
+                    index.parse().ok()?
+                };
+                let address = line.value()?.split(',').next().unwrap_or_default();
+
+                Some((index, address))
+            })
+            .collect();
+        addresses.sort_by_key(|(index, _)| *index);
+
+        let addresses: Vec<&str> = addresses.into_iter().map(|(_, address)| address).collect();
+        let address = *parse_networks(&addresses, "address")?
+            .first()
+            .ok_or(WireguardError::NoAddressProvided)?;
+
+        // DNS servers go first and search domains (which aren't IP addresses) after them, so
+        // they're exported back to `dns` and `dns-search` respectively
+        let list = |key: &str| ipv4.and_then(|ipv4| ipv4.get(key)).map(split_list);
+        let servers = list("dns").into_iter().flatten();
+        let search_domains = list("dns-search")
+            .into_iter()
+            .flatten()
+            .filter(|domain| domain.parse::<IpAddr>().is_err());
+
+        let mut dns: Vec<String> = Vec::new();
+        for value in servers.chain(search_domains) {
+            if !dns.iter().any(|dns| dns == value) {
+                dns.push(value.to_string());
+            }
+        }
+
+        Ok(builder.address(address).set_dns(dns).build())
+    }
+}
@@ -2,12 +2,30 @@ use either::Either;
 use ipnet::Ipv4Net;
 
 use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs};
 
+use super::document::metadata_field;
 use crate::prelude::*;
 
 #[must_use]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Peer {
+    /// Peer's name.
+    ///
+    /// Exported as `# Name = ...` comment. Line breaks are exported as spaces.
+    pub name: Option<String>,
+
+    /// Peer's description.
+    ///
+    /// Exported as `# Description = ...` comment. Line breaks are exported as spaces.
+    pub description: Option<String>,
+
+    /// Free-form comment lines.
+    ///
+    /// Each line is exported as `# ...` comment. Multi-line comments are exported as several
+    /// `# ...` comments.
+    pub comments: Vec<String>,
+
     /// Peer's endpoint.
     pub endpoint: Option<String>,
 
@@ -43,6 +61,13 @@ impl Peer {
     /// - [`WireguardError::NoAssignedIP`] -- no assigned ip found.
     ///   This means that your peer doesn't have allowed ip, that is in interface's addresses
     ///   network.
+    /// - [`WireguardError::AmneziaMismatch`] -- peer has AmneziaWG values, but server's
+    ///   interface is vanilla WireGuard. Error contains peer's name or public key.
+    ///
+    /// # Note
+    ///
+    /// AmneziaWG values, that must match on both ends (S1-S4 and H1-H4), are taken from
+    /// server's interface. Peer's own Jc, Jmin, Jmax and I1-I5 are kept.
     pub fn to_interface(&self, interface: &Interface) -> WireguardResult<Interface> {
         let Either::Left(private_key) = self.key.clone() else {
             return Err(WireguardError::NoPrivateKeyProvided);
@@ -57,16 +82,112 @@ impl Peer {
         Ok(Interface {
             address: assigned_ip,
             listen_port: None,
+            fwmark: None,
+            mtu: None,
             private_key,
             dns: interface.dns.clone(),
 
             #[cfg(feature = "amneziawg")]
-            amnezia_settings: self.amnezia_settings.clone(),
+            amnezia_settings: match (&self.amnezia_settings, &interface.amnezia_settings) {
+                (Some(own), Some(server)) => Some(own.with_shared_values(server)),
+                (Some(_), None) => {
+                    return Err(WireguardError::AmneziaMismatch(
+                        self.name
+                            .clone()
+                            .unwrap_or_else(|| self.public_key().to_string()),
+                    ));
+                }
+                (None, server) => server.clone(),
+            },
 
             endpoint: None,
             peers: vec![interface.to_peer()],
         })
     }
+
+    /// Get peer's [`PublicKey`].
+    ///
+    /// If peer has [`PrivateKey`], public key is derived from it.
+    #[must_use]
+    pub fn public_key(&self) -> PublicKey {
+        match &self.key {
+            Either::Left(private_key) => PublicKey::from(private_key),
+            Either::Right(public_key) => public_key.clone(),
+        }
+    }
+
+    /// Get peer's endpoint split to host and port.
+    ///
+    /// IPv6 addresses are returned without brackets.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- endpoint isn't `host:port`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let peer = PeerBuilder::new()
+    ///     .endpoint("[2001:db8::1]:51820".to_string())
+    ///     .build();
+    ///
+    /// assert_eq!(peer.endpoint_parts(), Ok(Some(("2001:db8::1", 51820))));
+    /// ```
+    pub fn endpoint_parts(&self) -> WireguardResult<Option<(&str, u16)>> {
+        let Some(endpoint) = &self.endpoint else {
+            return Ok(None);
+        };
+        let error = || WireguardError::InvalidValue("Endpoint".to_string());
+
+        let (host, port) = endpoint.rsplit_once(':').ok_or_else(error)?;
+        let host = match host.strip_prefix('[') {
+            Some(host) => host.strip_suffix(']').ok_or_else(error)?,
+            None => host,
+        };
+        if host.is_empty() {
+            return Err(error());
+        }
+
+        Ok(Some((host, port.parse().map_err(|_| error())?)))
+    }
+
+    /// Resolves peer's endpoint to socket address, using system's resolver.
+    ///
+    /// If endpoint's host resolves to several addresses, the first one is used.
+    ///
+    /// # Note
+    ///
+    /// It does blocking DNS lookup, if endpoint's host isn't IP address.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- endpoint can't be resolved.
+    pub fn resolve_endpoint(&self) -> WireguardResult<Option<SocketAddr>> {
+        self.endpoint
+            .as_ref()
+            .map(|endpoint| {
+                endpoint
+                    .to_socket_addrs()
+                    .ok()
+                    .and_then(|mut addresses| addresses.next())
+                    .ok_or(WireguardError::InvalidValue("Endpoint".to_string()))
+            })
+            .transpose()
+    }
+
+    /// Get [`PeerBuilder`] with all values of this peer.
+    ///
+    /// Use it for modifying existing peers through builder.
+    pub fn to_builder(&self) -> PeerBuilder {
+        PeerBuilder::from(self.clone())
+    }
+}
+
+/// Get `text` without line breaks, so it can't break out of single-line comment.
+pub(crate) fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
 }
 
 /// Implements [`fmt::Display`] for exporting peer.
@@ -78,6 +199,27 @@ impl Peer {
 impl fmt::Display for Peer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "[Peer]")?;
+        if let Some(name) = &self.name {
+            writeln!(f, "# Name = {}", single_line(name))?;
+        }
+        if let Some(description) = &self.description {
+            writeln!(f, "# Description = {}", single_line(description))?;
+        }
+        let mut lines = self
+            .comments
+            .iter()
+            .flat_map(|comment| comment.split(['\r', '\n']))
+            .peekable();
+        // comment, that looks like name or description, is separated from them by blank line,
+        // so it isn't parsed as one
+        if lines.peek().is_some_and(|line| {
+            metadata_field(line, "Name").is_some() || metadata_field(line, "Description").is_some()
+        }) {
+            writeln!(f)?;
+        }
+        for line in lines {
+            writeln!(f, "# {line}")?;
+        }
         if let Some(endpoint) = self.endpoint.clone() {
             writeln!(f, "Endpoint = {endpoint}")?;
         }
@@ -90,11 +232,7 @@ impl fmt::Display for Peer {
                 .collect::<Vec<String>>()
                 .join(",")
         )?;
-        writeln!(
-            f,
-            "PublicKey = {}",
-            self.key.clone().right_or_else(|key| PublicKey::from(&key))
-        )?;
+        writeln!(f, "PublicKey = {}", self.public_key())?;
         if let Some(preshared_key) = &self.preshared_key {
             writeln!(f, "PresharedKey = {preshared_key}")?;
         }
@@ -0,0 +1,131 @@
+use std::fmt::Write as _;
+
+use crate::prelude::*;
+
+/// Quotes RouterOS string value.
+///
+/// Control characters are escaped (`\n`, `\r`, `\t` or `\HH`), so value can't break out of
+/// script's line.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('"');
+    for char in value.chars() {
+        match char {
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(char);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            char if char.is_control() => {
+                for byte in char.encode_utf8(&mut [0; 4]).bytes() {
+                    let _ = write!(quoted, "\\{byte:02X}");
+                }
+            }
+            char => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+impl Interface {
+    /// Get MikroTik RouterOS script, that creates this interface named `name`: WireGuard
+    /// interface, its address and peers.
+    ///
+    /// Peer's name is exported as peer's comment.
+    ///
+    /// - [RouterOS WireGuard](https://help.mikrotik.com/docs/spaces/ROS/pages/69664792/WireGuard)
+    ///
+    /// # Note
+    ///
+    /// RouterOS doesn't support fwmark and DNS per interface, so they're ignored.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- peer's endpoint isn't `host:port`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let interface = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .listen_port(51820)
+    ///     .build();
+    ///
+    /// // paste it to RouterOS terminal, or `/import` it from file
+    /// let script = interface.to_routeros("wg0").unwrap();
+    ///
+    /// assert!(script.starts_with("/interface wireguard add name=\"wg0\" listen-port=51820"));
+    /// assert!(script.ends_with("/ip address add address=10.0.0.1/24 interface=\"wg0\"\n"));
+    /// ```
+    pub fn to_routeros(&self, name: &str) -> WireguardResult<String> {
+        let name = quote(name);
+        let mut script = String::new();
+
+        let _ = write!(script, "/interface wireguard add name={name}");
+        if let Some(listen_port) = self.listen_port {
+            let _ = write!(script, " listen-port={listen_port}");
+        }
+        if let Some(mtu) = self.mtu {
+            let _ = write!(script, " mtu={mtu}");
+        }
+        let _ = writeln!(
+            script,
+            " private-key={}",
+            quote(&self.private_key.to_string())
+        );
+
+        for peer in &self.peers {
+            let _ = write!(
+                script,
+                "/interface wireguard peers add interface={name} public-key={}",
+                quote(&peer.public_key().to_string())
+            );
+
+            let allowed_ips = peer
+                .allowed_ips
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(",");
+            let _ = write!(script, " allowed-address={allowed_ips}");
+
+            if let Some((host, port)) = peer.endpoint_parts()? {
+                let _ = write!(
+                    script,
+                    " endpoint-address={} endpoint-port={port}",
+                    quote(host)
+                );
+            }
+            if let Some(preshared_key) = &peer.preshared_key {
+                let _ = write!(
+                    script,
+                    " preshared-key={}",
+                    quote(&preshared_key.to_string())
+                );
+            }
+            if let Some(persistent_keepalive) = peer.persistent_keepalive {
+                let _ = write!(script, " persistent-keepalive={persistent_keepalive}s");
+            }
+            if let Some(comment) = &peer.name {
+                let _ = write!(script, " comment={}", quote(comment));
+            }
+
+            script.push('\n');
+        }
+
+        let _ = writeln!(
+            script,
+            "/ip address add address={} interface={name}",
+            self.address
+        );
+
+        Ok(script)
+    }
+}
@@ -0,0 +1,229 @@
+use ipnet::Ipv4Net;
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Peer's runtime status.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerStatus {
+    /// Peer's public key.
+    pub public_key: PublicKey,
+
+    /// Peer's preshared key.
+    pub preshared_key: Option<PresharedKey>,
+
+    /// Peer's current endpoint.
+    pub endpoint: Option<SocketAddr>,
+
+    /// Peer's allowed IPs.
+    ///
+    /// IPv6 networks are skipped, because they aren't supported yet.
+    pub allowed_ips: Vec<Ipv4Net>,
+
+    /// Time of the latest successful handshake.
+    pub latest_handshake: Option<SystemTime>,
+
+    /// Received bytes.
+    pub transfer_rx: u64,
+
+    /// Transmitted bytes.
+    pub transfer_tx: u64,
+
+    /// Peer's keepalive interval.
+    pub persistent_keepalive: Option<u32>,
+}
+
+/// Interface's runtime status.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// // output of `wg show wg0 dump`
+/// let dump = "\
+/// yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\tHIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=\t51820\toff
+/// xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t192.0.2.2:51820\t10.0.0.2/32\t1700000000\t2224\t38333\t25
+/// ";
+///
+/// let status: InterfaceStatus = dump.parse().unwrap();
+///
+/// assert_eq!(status.listen_port, 51820);
+/// assert_eq!(status.peers[0].transfer_rx, 2224);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceStatus {
+    /// Interface's private key.
+    pub private_key: Option<PrivateKey>,
+
+    /// Interface's public key.
+    pub public_key: Option<PublicKey>,
+
+    /// Interface's listen port.
+    pub listen_port: u16,
+
+    /// Interface's firewall mark.
+    pub fwmark: Option<u32>,
+
+    /// Interface's peers.
+    pub peers: Vec<PeerStatus>,
+}
+
+/// Parses `(none)`/`off` as [`None`].
+fn optional(value: &str) -> Option<&str> {
+    match value {
+        "(none)" | "off" => None,
+        value => Some(value),
+    }
+}
+
+/// Parses `value` of `key`, mapping failure to [`WireguardError::InvalidValue`].
+pub(crate) fn parse_value<T: FromStr>(value: &str, key: &str) -> WireguardResult<T> {
+    value
+        .parse()
+        .map_err(|_| WireguardError::InvalidValue(key.to_string()))
+}
+
+impl PeerStatus {
+    fn parse_dump_line(fields: [&str; 8]) -> WireguardResult<PeerStatus> {
+        let [public_key, preshared_key, endpoint, allowed_ips, handshake, rx, tx, keepalive] =
+            fields;
+
+        let latest_handshake = match parse_value(handshake, "latest-handshake")? {
+            0 => None,
+            seconds => Some(
+                SystemTime::UNIX_EPOCH
+                    .checked_add(Duration::from_secs(seconds))
+                    .ok_or(WireguardError::InvalidValue("latest-handshake".to_string()))?,
+            ),
+        };
+
+        Ok(PeerStatus {
+            public_key: PublicKey::try_from(public_key.to_string())?,
+            preshared_key: optional(preshared_key)
+                .map(|key| PresharedKey::try_from(key.to_string()))
+                .transpose()?,
+            endpoint: optional(endpoint)
+                .map(|endpoint| parse_value(endpoint, "endpoint"))
+                .transpose()?,
+            allowed_ips: optional(allowed_ips)
+                .into_iter()
+                .flat_map(|value| value.split(','))
+                .filter(|value| !value.contains(':'))
+                .map(|value| parse_value(value, "allowed-ips"))
+                .collect::<WireguardResult<_>>()?,
+            latest_handshake,
+            transfer_rx: parse_value(rx, "transfer-rx")?,
+            transfer_tx: parse_value(tx, "transfer-tx")?,
+            persistent_keepalive: optional(keepalive)
+                .map(|value| parse_value(value, "persistent-keepalive"))
+                .transpose()?,
+        })
+    }
+}
+
+impl InterfaceStatus {
+    /// Parses output of `wg show <interface> dump`.
+    ///
+    /// First line contains interface's private key, public key, listen port and fwmark.
+    /// Other lines contain peers' public key, preshared key, endpoint, allowed IPs, latest
+    /// handshake, received bytes, transmitted bytes and persistent keepalive. Values are
+    /// separated with tabs.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidLine`] -- line has wrong number of values, or dump is empty.
+    /// - [`WireguardError::InvalidValue`] -- value can't be parsed.
+    /// - [`WireguardError::InvalidPrivateKey`], [`WireguardError::InvalidPublicKey`],
+    ///   [`WireguardError::InvalidPresharedKey`] -- key can't be parsed.
+    pub fn parse_dump(dump: &str) -> WireguardResult<InterfaceStatus> {
+        let mut lines = dump.lines().filter(|line| !line.is_empty());
+
+        let fields: Vec<&str> = lines
+            .next()
+            .ok_or(WireguardError::InvalidLine(1))?
+            .split('\t')
+            .collect();
+        let &[private_key, public_key, listen_port, fwmark] = fields.as_slice() else {
+            return Err(WireguardError::InvalidLine(1));
+        };
+
+        let mut status = InterfaceStatus {
+            private_key: optional(private_key)
+                .map(|key| PrivateKey::try_from(key.to_string()))
+                .transpose()?,
+            public_key: optional(public_key)
+                .map(|key| PublicKey::try_from(key.to_string()))
+                .transpose()?,
+            listen_port: parse_value(listen_port, "listen-port")?,
+            fwmark: optional(fwmark)
+                .map(|fwmark| parse_fwmark(fwmark, "fwmark"))
+                .transpose()?,
+            peers: Vec::new(),
+        };
+
+        for (index, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let fields = <[&str; 8]>::try_from(fields.as_slice())
+                .map_err(|_| WireguardError::InvalidLine(index + 2))?;
+
+            status.peers.push(PeerStatus::parse_dump_line(fields)?);
+        }
+
+        Ok(status)
+    }
+
+    /// Get peer's status by its public key.
+    #[must_use]
+    pub fn peer(&self, public_key: &PublicKey) -> Option<&PeerStatus> {
+        self.peers
+            .iter()
+            .find(|peer| peer.public_key == *public_key)
+    }
+}
+
+/// Parses fwmark `value` of `key` in hex (`0x...`) or decimal, mapping failure to
+/// [`WireguardError::InvalidValue`].
+pub(crate) fn parse_fwmark(value: &str, key: &str) -> WireguardResult<u32> {
+    match value.strip_prefix("0x") {
+        Some(hex) => {
+            u32::from_str_radix(hex, 16).map_err(|_| WireguardError::InvalidValue(key.to_string()))
+        }
+        None => parse_value(value, key),
+    }
+}
+
+impl FromStr for InterfaceStatus {
+    type Err = WireguardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        InterfaceStatus::parse_dump(s)
+    }
+}
+
+impl Peer {
+    /// Get this peer's status from interface's status (matched by [`PublicKey`]).
+    #[must_use]
+    pub fn status<'a>(&self, status: &'a InterfaceStatus) -> Option<&'a PeerStatus> {
+        status.peer(&self.public_key())
+    }
+}
+
+impl Interface {
+    /// Get configured peers with their statuses (matched by [`PublicKey`]).
+    ///
+    /// Status is [`None`], if peer isn't found in interface's status.
+    #[must_use]
+    pub fn peers_status<'a>(
+        &'a self,
+        status: &'a InterfaceStatus,
+    ) -> Vec<(&'a Peer, Option<&'a PeerStatus>)> {
+        self.peers
+            .iter()
+            .map(|peer| (peer, peer.status(status)))
+            .collect()
+    }
+}
@@ -0,0 +1,390 @@
+use std::fmt;
+
+use super::document::parse_networks;
+use super::peer::single_line;
+use super::status::{parse_fwmark, parse_value};
+use crate::prelude::*;
+
+/// OpenWrt UCI network config (`/etc/config/network` entries).
+///
+/// Use [`fmt::Display`] for exporting and [`Interface::from_uci()`] for importing.
+///
+/// Exports `config interface '<name>'` section with `proto 'wireguard'` and
+/// `config wireguard_<name>` section for every peer. Peer's description is exported as peer's
+/// `description`; peer's name isn't exported, because UCI doesn't have it.
+///
+/// - [OpenWrt WireGuard](https://openwrt.org/docs/guide-user/network/wireguard/basics)
+///
+/// # Note
+///
+/// Peer's endpoint must be `host:port`, otherwise it's exported as `endpoint_host` only.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let interface = InterfaceBuilder::new()
+///     .address("10.0.0.1/24".parse().unwrap())
+///     .listen_port(51820)
+///     .build();
+///
+/// let config = interface.to_uci("wg0").to_string();
+///
+/// assert!(config.starts_with("config interface 'wg0'\n\toption proto 'wireguard'\n"));
+///
+/// let imported = Interface::from_uci(&config, "wg0").unwrap();
+/// assert_eq!(imported.private_key, interface.private_key);
+/// ```
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct UciConfig<'a> {
+    interface: &'a Interface,
+    name: String,
+    route_allowed_ips: bool,
+}
+
+impl<'a> UciConfig<'a> {
+    /// Creates config for `interface` named `name` (e.g. `wg0`).
+    pub fn new(interface: &'a Interface, name: String) -> Self {
+        Self {
+            interface,
+            name,
+            route_allowed_ips: true,
+        }
+    }
+
+    /// Sets peers' `route_allowed_ips`: whether routes to allowed IPs are created.
+    ///
+    /// Default is `true`.
+    pub fn route_allowed_ips(mut self, route_allowed_ips: bool) -> Self {
+        self.route_allowed_ips = route_allowed_ips;
+        self
+    }
+}
+
+/// Quotes UCI value.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Implements [`fmt::Display`] for exporting config.
+impl fmt::Display for UciConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let interface = self.interface;
+
+        writeln!(f, "config interface {}", quote(&self.name))?;
+        writeln!(f, "\toption proto 'wireguard'")?;
+        writeln!(
+            f,
+            "\toption private_key {}",
+            quote(&interface.private_key.to_string())
+        )?;
+        if let Some(listen_port) = interface.listen_port {
+            writeln!(f, "\toption listen_port '{listen_port}'")?;
+        }
+        if let Some(fwmark) = interface.fwmark {
+            writeln!(f, "\toption fwmark '{fwmark}'")?;
+        }
+        if let Some(mtu) = interface.mtu {
+            writeln!(f, "\toption mtu '{mtu}'")?;
+        }
+        writeln!(f, "\tlist addresses '{}'", interface.address)?;
+        for dns in &interface.dns {
+            writeln!(f, "\tlist dns {}", quote(dns))?;
+        }
+
+        for peer in &interface.peers {
+            writeln!(f)?;
+            writeln!(f, "config wireguard_{}", self.name)?;
+            if let Some(description) = &peer.description {
+                writeln!(
+                    f,
+                    "\toption description {}",
+                    quote(&single_line(description))
+                )?;
+            }
+            writeln!(f, "\toption public_key '{}'", peer.public_key())?;
+            if let Some(preshared_key) = &peer.preshared_key {
+                writeln!(f, "\toption preshared_key '{preshared_key}'")?;
+            }
+            match (peer.endpoint_parts(), &peer.endpoint) {
+                (Ok(Some((host, port))), _) => {
+                    writeln!(f, "\toption endpoint_host {}", quote(host))?;
+                    writeln!(f, "\toption endpoint_port '{port}'")?;
+                }
+                (Err(_), Some(endpoint)) => {
+                    writeln!(f, "\toption endpoint_host {}", quote(endpoint))?;
+                }
+                _ => {}
+            }
+            if let Some(persistent_keepalive) = peer.persistent_keepalive {
+                writeln!(f, "\toption persistent_keepalive '{persistent_keepalive}'")?;
+            }
+            writeln!(
+                f,
+                "\toption route_allowed_ips '{}'",
+                u8::from(self.route_allowed_ips)
+            )?;
+            for allowed_ip in &peer.allowed_ips {
+                writeln!(f, "\tlist allowed_ips '{allowed_ip}'")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Section of UCI config: `config <kind> ['<name>']`.
+struct UciSection {
+    kind: String,
+    name: Option<String>,
+    options: Vec<(String, String)>,
+}
+
+impl UciSection {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn get_all(&self, key: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    fn parse_number<T: std::str::FromStr>(&self, key: &str) -> WireguardResult<Option<T>> {
+        self.get(key)
+            .map(|value| parse_value(value, key))
+            .transpose()
+    }
+}
+
+/// Splits line into words, handling quotes (`'...'`, `"..."`) and escapes, like shell does.
+fn split_words(line: &str, line_number: usize) -> WireguardResult<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '#' if word.is_none() => break,
+            char if char.is_whitespace() => {
+                words.extend(word.take());
+            }
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(char) => word.push(char),
+                        None => return Err(WireguardError::InvalidLine(line_number)),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => word.push(
+                            chars
+                                .next()
+                                .ok_or(WireguardError::InvalidLine(line_number))?,
+                        ),
+                        Some(char) => word.push(char),
+                        None => return Err(WireguardError::InvalidLine(line_number)),
+                    }
+                }
+            }
+            '\\' => {
+                let escaped = chars
+                    .next()
+                    .ok_or(WireguardError::InvalidLine(line_number))?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            char => word.get_or_insert_with(String::new).push(char),
+        }
+    }
+    words.extend(word);
+
+    Ok(words)
+}
+
+fn parse_uci(config: &str) -> WireguardResult<Vec<UciSection>> {
+    let mut sections: Vec<UciSection> = Vec::new();
+
+    for (index, line) in config.lines().enumerate() {
+        let line_number = index + 1;
+        let words = split_words(line, line_number)?;
+
+        match words.as_slice() {
+            [] => {}
+            // `uci export` starts output with package's name
+            [keyword, _] if keyword == "package" => {}
+            [keyword, kind, rest @ ..] if keyword == "config" && rest.len() <= 1 => {
+                sections.push(UciSection {
+                    kind: kind.clone(),
+                    name: rest.first().cloned(),
+                    options: Vec::new(),
+                });
+            }
+            [keyword, key, value] if keyword == "option" || keyword == "list" => {
+                let section = sections
+                    .last_mut()
+                    .ok_or(WireguardError::InvalidLine(line_number))?;
+
+                if keyword == "option" {
+                    section.options.retain(|(k, _)| k != key);
+                }
+                section.options.push((key.clone(), value.clone()));
+            }
+            _ => return Err(WireguardError::InvalidLine(line_number)),
+        }
+    }
+
+    Ok(sections)
+}
+
+fn peer_from_section(section: &UciSection) -> WireguardResult<Peer> {
+    let public_key = section
+        .get("public_key")
+        .ok_or(WireguardError::NoPublicKeyProvided)?;
+
+    let allowed_ips: Vec<&str> = section
+        .get_all("allowed_ips")
+        .into_iter()
+        .flat_map(str::split_whitespace)
+        .collect();
+
+    let mut builder = PeerBuilder::new()
+        .public_key(PublicKey::try_from(public_key.to_string())?)
+        .set_allowed_ips(parse_networks(&allowed_ips, "allowed_ips")?);
+
+    if let Some(description) = section.get("description") {
+        builder = builder.description(description.to_string());
+    }
+    if let Some(preshared_key) = section.get("preshared_key") {
+        builder = builder.preshared_key(PresharedKey::try_from(preshared_key.to_string())?);
+    }
+    if let Some(host) = section.get("endpoint_host") {
+        let port = section
+            .parse_number::<u16>("endpoint_port")?
+            .unwrap_or(51820);
+        let endpoint = if host.contains(':') {
+            format!("[{host}]:{port}")
+        } else {
+            format!("{host}:{port}")
+        };
+
+        builder = builder.endpoint(endpoint);
+    }
+    if let Some(persistent_keepalive) = section.parse_number("persistent_keepalive")? {
+        if persistent_keepalive != 0 {
+            builder = builder.persistent_keepalive(persistent_keepalive);
+        }
+    }
+
+    Ok(builder.build())
+}
+
+impl Interface {
+    /// Get OpenWrt UCI config for this interface named `name`.
+    ///
+    /// See [`UciConfig`].
+    pub fn to_uci(&self, name: &str) -> UciConfig<'_> {
+        UciConfig::new(self, name.to_string())
+    }
+
+    /// Imports interface named `name` from OpenWrt UCI config (e.g. `/etc/config/network`).
+    ///
+    /// Peers are read from `config wireguard_<name>` sections. Disabled peers
+    /// (`option disabled '1'`), unknown options and sections are ignored, as well as `package`
+    /// lines of `uci export` output. Peer's `description` is imported as peer's description.
+    /// Missing `endpoint_port` defaults to `51820`.
+    ///
+    /// # Note
+    ///
+    /// IPv6 addresses and allowed IPs are skipped, because they aren't supported yet. If
+    /// interface has multiple IPv4 addresses, the first one is used.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidLine`] -- config can't be parsed.
+    /// - [`WireguardError::MissingSection`] -- there's no WireGuard interface named `name`.
+    /// - [`WireguardError::NoPrivateKeyProvided`] -- there's no `private_key`.
+    /// - [`WireguardError::NoAddressProvided`] -- there's no IPv4 address.
+    /// - [`WireguardError::NoPublicKeyProvided`] -- peer doesn't have `public_key`.
+    /// - [`WireguardError::InvalidValue`] -- value can't be parsed.
+    /// - [`WireguardError::InvalidPrivateKey`], [`WireguardError::InvalidPublicKey`],
+    ///   [`WireguardError::InvalidPresharedKey`] -- key can't be parsed.
+    pub fn from_uci(config: &str, name: &str) -> WireguardResult<Interface> {
+        let sections = parse_uci(config)?;
+
+        let section = sections
+            .iter()
+            .find(|section| {
+                section.kind == "interface"
+                    && section.name.as_deref() == Some(name)
+                    && section.get("proto") == Some("wireguard")
+            })
+            .ok_or(WireguardError::MissingSection(format!(
+                "interface '{name}'"
+            )))?;
+
+        let private_key = section
+            .get("private_key")
+            .ok_or(WireguardError::NoPrivateKeyProvided)?;
+
+        // `addresses` is list, but also can be space-separated option
+        let addresses: Vec<&str> = section
+            .get_all("addresses")
+            .into_iter()
+            .flat_map(str::split_whitespace)
+            .collect();
+        let address = *parse_networks(&addresses, "addresses")?
+            .first()
+            .ok_or(WireguardError::NoAddressProvided)?;
+
+        let mut builder = InterfaceBuilder::new()
+            .address(address)
+            .private_key(PrivateKey::try_from(private_key.to_string())?)
+            .set_dns(
+                section
+                    .get_all("dns")
+                    .into_iter()
+                    .flat_map(str::split_whitespace)
+                    .map(ToString::to_string)
+                    .collect(),
+            );
+
+        if let Some(listen_port) = section.parse_number("listen_port")? {
+            builder = builder.listen_port(listen_port);
+        }
+        if let Some(fwmark) = section.get("fwmark") {
+            let fwmark = parse_fwmark(fwmark, "fwmark")?;
+
+            if fwmark != 0 {
+                builder = builder.fwmark(fwmark);
+            }
+        }
+        if let Some(mtu) = section.parse_number("mtu")? {
+            builder = builder.mtu(mtu);
+        }
+
+        let peer_kind = format!("wireguard_{name}");
+        for section in &sections {
+            if section.kind == peer_kind && section.get("disabled") != Some("1") {
+                builder = builder.add_peer(peer_from_section(section)?);
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
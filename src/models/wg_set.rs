@@ -0,0 +1,268 @@
+use ipnet::Ipv4Net;
+
+use crate::prelude::*;
+
+/// Path, that is used instead of key file. Key itself is passed through stdin
+/// (see [`WgSetCommand::stdin()`]).
+const STDIN_PATH: &str = "/dev/stdin";
+
+/// Peer's values to set with `wg set <interface> peer <public-key> ...`.
+///
+/// [`None`] means that value isn't changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerUpdate {
+    /// Peer's public key.
+    pub public_key: PublicKey,
+
+    /// New endpoint.
+    pub endpoint: Option<String>,
+
+    /// New allowed IPs. Replaces all current allowed IPs.
+    pub allowed_ips: Option<Vec<Ipv4Net>>,
+
+    /// New preshared key. `Some(None)` removes preshared key.
+    pub preshared_key: Option<Option<PresharedKey>>,
+
+    /// New persistent keepalive. `Some(None)` turns it off.
+    pub persistent_keepalive: Option<Option<u32>>,
+}
+
+impl PeerUpdate {
+    fn new(public_key: PublicKey) -> Self {
+        Self {
+            public_key,
+            endpoint: None,
+            allowed_ips: None,
+            preshared_key: None,
+            persistent_keepalive: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.endpoint.is_none()
+            && self.allowed_ips.is_none()
+            && self.preshared_key.is_none()
+            && self.persistent_keepalive.is_none()
+    }
+}
+
+/// Single `wg set` command.
+///
+/// Use [`Interface::wg_set_commands()`] for generating commands, [`WgSetCommand::args()`] for
+/// getting `wg` arguments and [`WgSetCommand::stdin()`] for getting key, that must be passed
+/// to command's stdin.
+///
+/// [wg(8)](https://man7.org/linux/man-pages/man8/wg.8.html)
+#[derive(Clone, Debug, PartialEq)]
+pub enum WgSetCommand {
+    /// `wg set <interface> listen-port <port>`. Port `0` means random port.
+    ListenPort(u16),
+
+    /// `wg set <interface> fwmark <fwmark>`. Fwmark `0` turns it off.
+    FwMark(u32),
+
+    /// `wg set <interface> private-key /dev/stdin`.
+    PrivateKey(PrivateKey),
+
+    /// `wg set <interface> peer <public-key> remove`.
+    RemovePeer(PublicKey),
+
+    /// `wg set <interface> peer <public-key> [endpoint ...] [allowed-ips ...] ...`.
+    UpdatePeer(PeerUpdate),
+}
+
+impl WgSetCommand {
+    /// Get `wg` arguments (starting with `set`) for `interface`.
+    ///
+    /// Keys are read from `/dev/stdin`, see [`WgSetCommand::stdin()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let command = WgSetCommand::ListenPort(51820);
+    ///
+    /// assert_eq!(command.args("wg0"), vec!["set", "wg0", "listen-port", "51820"]);
+    /// assert_eq!(command.stdin(), None);
+    /// ```
+    #[must_use]
+    pub fn args(&self, interface: &str) -> Vec<String> {
+        let mut args = vec!["set".to_string(), interface.to_string()];
+
+        match self {
+            WgSetCommand::ListenPort(listen_port) => {
+                args.extend(["listen-port".to_string(), listen_port.to_string()]);
+            }
+            WgSetCommand::FwMark(fwmark) => {
+                args.extend(["fwmark".to_string(), fwmark.to_string()]);
+            }
+            WgSetCommand::PrivateKey(_) => {
+                args.extend(["private-key".to_string(), STDIN_PATH.to_string()]);
+            }
+            WgSetCommand::RemovePeer(public_key) => {
+                args.extend([
+                    "peer".to_string(),
+                    public_key.to_string(),
+                    "remove".to_string(),
+                ]);
+            }
+            WgSetCommand::UpdatePeer(update) => {
+                args.extend(["peer".to_string(), update.public_key.to_string()]);
+
+                if let Some(endpoint) = &update.endpoint {
+                    args.extend(["endpoint".to_string(), endpoint.clone()]);
+                }
+                if let Some(allowed_ips) = &update.allowed_ips {
+                    args.extend([
+                        "allowed-ips".to_string(),
+                        allowed_ips
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<String>>()
+                            .join(","),
+                    ]);
+                }
+                if let Some(preshared_key) = &update.preshared_key {
+                    let path = if preshared_key.is_some() {
+                        STDIN_PATH
+                    } else {
+                        "/dev/null"
+                    };
+
+                    args.extend(["preshared-key".to_string(), path.to_string()]);
+                }
+                if let Some(persistent_keepalive) = update.persistent_keepalive {
+                    args.extend([
+                        "persistent-keepalive".to_string(),
+                        persistent_keepalive.map_or("off".to_string(), |v| v.to_string()),
+                    ]);
+                }
+            }
+        }
+
+        args
+    }
+
+    /// Get key, that must be written to command's stdin (if command sets key).
+    #[must_use]
+    pub fn stdin(&self) -> Option<String> {
+        match self {
+            WgSetCommand::PrivateKey(private_key) => Some(private_key.to_string()),
+            WgSetCommand::UpdatePeer(PeerUpdate {
+                preshared_key: Some(Some(preshared_key)),
+                ..
+            }) => Some(preshared_key.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl Interface {
+    /// Get `wg set` commands, that transition running interface from this (old) config to `new`
+    /// config without restart.
+    ///
+    /// Commands are ordered: interface's changes, removed peers, modified peers and added peers.
+    ///
+    /// # Note
+    ///
+    /// `wg set` configures only WireGuard device, so changes of address, DNS, MTU and interface's
+    /// endpoint are ignored. Also `wg set` can't unset peer's endpoint, so removed endpoints are
+    /// ignored too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let old = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .build();
+    ///
+    /// let peer = PeerBuilder::new()
+    ///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+    ///     .build();
+    /// let new = old.to_builder().add_peer(peer.clone()).build();
+    ///
+    /// let commands = old.wg_set_commands(&new);
+    ///
+    /// assert_eq!(
+    ///     commands[0].args("wg0"),
+    ///     vec![
+    ///         "set".to_string(),
+    ///         "wg0".to_string(),
+    ///         "peer".to_string(),
+    ///         peer.public_key().to_string(),
+    ///         "allowed-ips".to_string(),
+    ///         "10.0.0.2/32".to_string(),
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn wg_set_commands(&self, new: &Interface) -> Vec<WgSetCommand> {
+        let diff = self.diff(new);
+        let mut commands = Vec::new();
+
+        for change in &diff.changes {
+            match change {
+                InterfaceChange::ListenPort { new, .. } => {
+                    commands.push(WgSetCommand::ListenPort(new.unwrap_or(0)));
+                }
+                InterfaceChange::FwMark { new, .. } => {
+                    commands.push(WgSetCommand::FwMark(new.unwrap_or(0)));
+                }
+                InterfaceChange::PrivateKey => {
+                    commands.push(WgSetCommand::PrivateKey(new.private_key.clone()));
+                }
+                _ => {}
+            }
+        }
+
+        for peer in &diff.removed_peers {
+            commands.push(WgSetCommand::RemovePeer(peer.public_key()));
+        }
+
+        for peer_diff in &diff.modified_peers {
+            let Some(peer) = new
+                .peers
+                .iter()
+                .find(|peer| peer.public_key() == peer_diff.public_key)
+            else {
+                continue;
+            };
+
+            let mut update = PeerUpdate::new(peer_diff.public_key.clone());
+            for change in &peer_diff.changes {
+                match change {
+                    PeerChange::AllowedIPs { .. } => {
+                        update.allowed_ips = Some(peer.allowed_ips.clone());
+                    }
+                    PeerChange::Endpoint { new, .. } => update.endpoint.clone_from(new),
+                    PeerChange::PresharedKey { new, .. } => {
+                        update.preshared_key = Some(new.clone());
+                    }
+                    PeerChange::PersistentKeepalive { new, .. } => {
+                        update.persistent_keepalive = Some(*new);
+                    }
+                    _ => {}
+                }
+            }
+
+            if !update.is_empty() {
+                commands.push(WgSetCommand::UpdatePeer(update));
+            }
+        }
+
+        for peer in &diff.added_peers {
+            commands.push(WgSetCommand::UpdatePeer(PeerUpdate {
+                public_key: peer.public_key(),
+                endpoint: peer.endpoint.clone(),
+                allowed_ips: Some(peer.allowed_ips.clone()),
+                preshared_key: peer.preshared_key.clone().map(Some),
+                persistent_keepalive: peer.persistent_keepalive.map(Some),
+            }));
+        }
+
+        commands
+    }
+}
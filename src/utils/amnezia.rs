@@ -1,7 +1,11 @@
 use rand::prelude::*;
-use std::{collections::HashSet, fmt};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::WireguardError;
+use crate::{Interface, Peer, WireguardError};
 
 use super::WireguardResult;
 
@@ -17,7 +21,7 @@ macro_rules! assert_return {
 ///
 /// - [Documentation](https://github.com/amnezia-vpn/amneziawg-linux-kernel-module?tab=readme-ov-file#configuration)
 #[must_use]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AmneziaSettings {
     /// 1 ≤ Jc ≤ 128; recommended range is from 3 to 10 inclusive
     pub jc: usize,
@@ -30,21 +34,331 @@ pub struct AmneziaSettings {
     pub s1: usize,
     /// S2 < 1280; recommended range is from 15 to 150 inclusive
     pub s2: usize,
+    /// S3 < 1280; S3 + 64 ≠ S1 + 148 and S2 + 92; recommended range is from 15 to 150 inclusive
+    ///
+    /// Padding of cookie reply packets (AmneziaWG 2.0).
+    pub s3: Option<usize>,
+    /// S4 < 1280; recommended range is from 1 to 32 inclusive
+    ///
+    /// Padding of transport packets (AmneziaWG 2.0).
+    pub s4: Option<usize>,
+
+    /// must not overlap with each other; recommended range is from 5 to 2147483647 inclusive
+    pub h1: MagicHeader,
+    /// must not overlap with each other; recommended range is from 5 to 2147483647 inclusive
+    pub h2: MagicHeader,
+    /// must not overlap with each other; recommended range is from 5 to 2147483647 inclusive
+    pub h3: MagicHeader,
+    /// must not overlap with each other; recommended range is from 5 to 2147483647 inclusive
+    pub h4: MagicHeader,
+
+    /// Signature packets, that are sent before handshake (AmneziaWG 1.5).
+    pub i1: Option<SignaturePacket>,
+    /// See [`AmneziaSettings::i1`].
+    pub i2: Option<SignaturePacket>,
+    /// See [`AmneziaSettings::i1`].
+    pub i3: Option<SignaturePacket>,
+    /// See [`AmneziaSettings::i1`].
+    pub i4: Option<SignaturePacket>,
+    /// See [`AmneziaSettings::i1`].
+    pub i5: Option<SignaturePacket>,
+}
+
+/// Keys of all values in config, in config's order.
+const KEYS: [&str; 16] = [
+    "Jc", "Jmin", "Jmax", "S1", "S2", "S3", "S4", "H1", "H2", "H3", "H4", "I1", "I2", "I3", "I4",
+    "I5",
+];
+
+/// Maximum size of packet.
+const MAX_PACKET_SIZE: usize = 1280;
+
+/// Sizes of handshake initiation, handshake response and cookie reply packets without padding.
+const INITIATION_SIZE: usize = 148;
+const RESPONSE_SIZE: usize = 92;
+const COOKIE_REPLY_SIZE: usize = 64;
+
+/// Value of AmneziaWG magic header (H1-H4): single value or inclusive range (`start-end`), from
+/// which header is randomly chosen for every packet (AmneziaWG 2.0).
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let single = MagicHeader::from(1234);
+/// let range: MagicHeader = "1000-2000".parse().unwrap();
+///
+/// assert_eq!(single.to_string(), "1234");
+/// assert_eq!(range, MagicHeader::new(1000, 2000));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MagicHeader {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl MagicHeader {
+    /// Creates inclusive range of headers.
+    #[must_use]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Checks whether ranges overlap.
+    #[must_use]
+    pub fn overlaps(&self, other: &MagicHeader) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl From<usize> for MagicHeader {
+    fn from(value: usize) -> Self {
+        Self::new(value, value)
+    }
+}
+
+impl fmt::Display for MagicHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+impl FromStr for MagicHeader {
+    type Err = WireguardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || WireguardError::InvalidAmneziaSetting(s.to_string());
+        let parse = |value: &str| value.trim().parse::<usize>().map_err(|_| error());
+
+        match s.split_once('-') {
+            Some((start, end)) => Ok(Self::new(parse(start)?, parse(end)?)),
+            None => Ok(Self::from(parse(s)?)),
+        }
+    }
+}
+
+/// Tag of [`SignaturePacket`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureTag {
+    /// `<b 0x...>` -- static bytes.
+    Bytes(Vec<u8>),
+    /// `<r N>` -- `N` random bytes.
+    Random(usize),
+    /// `<t>` -- timestamp (4 bytes).
+    Timestamp,
+    /// `<c>` -- packet counter (4 bytes).
+    Counter,
+}
+
+impl SignatureTag {
+    /// Get size of tag's bytes in packet.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            SignatureTag::Bytes(bytes) => bytes.len(),
+            SignatureTag::Random(len) => *len,
+            SignatureTag::Timestamp | SignatureTag::Counter => 4,
+        }
+    }
 
-    /// must be unique among each other; recommended range is from 5 to 2147483647 inclusive
-    pub h1: usize,
-    /// must be unique among each other; recommended range is from 5 to 2147483647 inclusive
-    pub h2: usize,
-    /// must be unique among each other; recommended range is from 5 to 2147483647 inclusive
-    pub h3: usize,
-    /// must be unique among each other; recommended range is from 5 to 2147483647 inclusive
-    pub h4: usize,
+    /// Checks whether tag doesn't produce any bytes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for SignatureTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureTag::Bytes(bytes) => {
+                write!(f, "<b 0x")?;
+                for byte in bytes {
+                    write!(f, "{byte:02x}")?;
+                }
+                write!(f, ">")
+            }
+            SignatureTag::Random(len) => write!(f, "<r {len}>"),
+            SignatureTag::Timestamp => write!(f, "<t>"),
+            SignatureTag::Counter => write!(f, "<c>"),
+        }
+    }
+}
+
+impl FromStr for SignatureTag {
+    type Err = WireguardError;
+
+    /// Parses tag without angle brackets (e.g. `b 0xf6ab`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || WireguardError::InvalidAmneziaSetting(format!("<{s}>"));
+
+        let mut parts = s.split_whitespace();
+        let tag = match (parts.next(), parts.next()) {
+            (Some("b"), Some(hex)) => {
+                let hex = hex
+                    .strip_prefix("0x")
+                    .or_else(|| hex.strip_prefix("0X"))
+                    .ok_or_else(error)?;
+                if hex.is_empty() || hex.len() % 2 != 0 || !hex.is_ascii() {
+                    return Err(error());
+                }
+
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| error())?;
+
+                SignatureTag::Bytes(bytes)
+            }
+            (Some("r"), Some(len)) => SignatureTag::Random(len.parse().map_err(|_| error())?),
+            (Some("t"), None) => SignatureTag::Timestamp,
+            (Some("c"), None) => SignatureTag::Counter,
+            _ => return Err(error()),
+        };
+
+        if parts.next().is_some() {
+            return Err(error());
+        }
+
+        Ok(tag)
+    }
+}
+
+/// AmneziaWG signature packet (I1-I5): template of packet, that is sent before handshake for
+/// imitating other protocols.
+///
+/// Template consists of tags: `<b 0x...>` (static bytes), `<r N>` (`N` random bytes), `<t>`
+/// (timestamp) and `<c>` (counter).
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let packet: SignaturePacket = "<b 0xc0ff><r 16><t>".parse().unwrap();
+///
+/// assert_eq!(
+///     packet.tags,
+///     vec![
+///         SignatureTag::Bytes(vec![0xc0, 0xff]),
+///         SignatureTag::Random(16),
+///         SignatureTag::Timestamp,
+///     ]
+/// );
+/// assert_eq!(packet.len(), 22);
+/// assert_eq!(packet.to_string(), "<b 0xc0ff><r 16><t>");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignaturePacket {
+    pub tags: Vec<SignatureTag>,
+}
+
+impl SignaturePacket {
+    /// Get size of packet.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tags.iter().map(SignatureTag::len).sum()
+    }
+
+    /// Checks whether packet is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get packet's bytes: random bytes are generated, `<t>` is replaced with current UNIX time
+    /// and `<c>` with `counter` (both are 4 bytes, big-endian).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let packet: SignaturePacket = "<b 0xc0ff><r 4><c>".parse().unwrap();
+    /// let bytes = packet.to_bytes(7);
+    ///
+    /// assert_eq!(bytes.len(), 10);
+    /// assert_eq!(bytes[..2], [0xc0, 0xff]);
+    /// assert_eq!(bytes[6..], [0, 0, 0, 7]);
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self, counter: u32) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+
+        for tag in &self.tags {
+            match tag {
+                SignatureTag::Bytes(tag_bytes) => bytes.extend(tag_bytes),
+                SignatureTag::Random(len) => {
+                    let start = bytes.len();
+                    bytes.resize(start + len, 0);
+                    rand::rng().fill_bytes(&mut bytes[start..]);
+                }
+                SignatureTag::Timestamp => {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    bytes.extend(u32::try_from(now).unwrap_or(u32::MAX).to_be_bytes());
+                }
+                SignatureTag::Counter => bytes.extend(counter.to_be_bytes()),
+            }
+        }
+
+        bytes
+    }
+}
+
+impl fmt::Display for SignaturePacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for tag in &self.tags {
+            write!(f, "{tag}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SignaturePacket {
+    type Err = WireguardError;
+
+    /// Parses packet's template.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidAmneziaSetting`] -- template has unknown or malformed tag, or
+    ///   text outside of tags.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = Vec::new();
+        let mut rest = s.trim();
+
+        while !rest.is_empty() {
+            let tag = rest
+                .strip_prefix('<')
+                .and_then(|tag| tag.split_once('>'))
+                .ok_or_else(|| WireguardError::InvalidAmneziaSetting(rest.to_string()))?;
+
+            tags.push(tag.0.parse()?);
+            rest = tag.1.trim_start();
+        }
+
+        Ok(Self { tags })
+    }
 }
 
 /// Methods
 impl AmneziaSettings {
     /// Generate [`AmneziaSettings`] with randomized values, based of recommended ranges or values.
     ///
+    /// Generated values always pass [`AmneziaSettings::validate()`]: S1 + 56 ≠ S2 and H1-H4 are
+    /// unique. S3, S4 and I1-I5 aren't set, so settings are compatible with AmneziaWG 1.0.
+    /// Use [`AmneziaSettingsBuilder`] for pinning some of values.
+    ///
     /// # Examples
     ///
     /// ```
@@ -57,39 +371,11 @@ impl AmneziaSettings {
     ///    .amnezia_settings(settings)
     ///    .build();
     /// ```
+    #[allow(clippy::missing_panics_doc)] // nothing is pinned, so headers always have free space
     pub fn random() -> Self {
-        let mut rng = rand::rng();
-
-        let jc = rng.random_range(3..=10);
-        let jmin = rng.random_range(40..=60);
-        let jmax = rng.random_range((jmin + 10)..=90);
-        let s1 = rng.random_range(15..=150);
-        let s2 = {
-            let mut value = s1 + 56;
-
-            while s1 + 56 == value {
-                value = rng.random_range(1..=150);
-            }
-
-            value
-        };
-
-        let h1 = rng.random_range(10..=2_147_483_640);
-        let h2 = rng.random_range(10..=2_147_483_640);
-        let h3 = rng.random_range(10..=2_147_483_640);
-        let h4 = rng.random_range(10..=2_147_483_640);
-
-        Self {
-            jc,
-            jmin,
-            jmax,
-            s1,
-            s2,
-            h1,
-            h2,
-            h3,
-            h4,
-        }
+        AmneziaSettingsBuilder::new()
+            .generate()
+            .expect("default headers always leave free space")
     }
 
     /// Validates [`AmneziaSettings`].
@@ -113,26 +399,183 @@ impl AmneziaSettings {
             WireguardError::InvalidAmneziaSetting("Jmax".to_string())
         );
         assert_return!(
-            self.s1 < 1280 && self.s1 + 56 != self.s2,
+            self.s1 < 1280 && INITIATION_SIZE + self.s1 != RESPONSE_SIZE + self.s2,
             WireguardError::InvalidAmneziaSetting("S1".to_string())
         );
         assert_return!(
             self.s2 < 1280,
             WireguardError::InvalidAmneziaSetting("S2".to_string())
         );
+        if let Some(s3) = self.s3 {
+            assert_return!(
+                s3 < 1280
+                    && COOKIE_REPLY_SIZE + s3 != INITIATION_SIZE + self.s1
+                    && COOKIE_REPLY_SIZE + s3 != RESPONSE_SIZE + self.s2,
+                WireguardError::InvalidAmneziaSetting("S3".to_string())
+            );
+        }
+        if let Some(s4) = self.s4 {
+            assert_return!(
+                s4 < 1280,
+                WireguardError::InvalidAmneziaSetting("S4".to_string())
+            );
+        }
 
-        let are_h_values_unique = {
-            let set = HashSet::from([self.h1, self.h2, self.h3, self.h4]);
+        let headers = [self.h1, self.h2, self.h3, self.h4];
+        for (index, header) in headers.iter().enumerate() {
+            assert_return!(
+                header.start <= header.end && u32::try_from(header.end).is_ok(),
+                WireguardError::InvalidAmneziaSetting(format!("H{}", index + 1))
+            );
+        }
 
-            set.len() == 4
-        };
+        let are_h_values_unique = headers.iter().enumerate().all(|(index, header)| {
+            headers[index + 1..]
+                .iter()
+                .all(|other| !header.overlaps(other))
+        });
         assert_return!(
             are_h_values_unique,
             WireguardError::InvalidAmneziaSetting("H1/H2/H3/H4".to_string())
         );
 
+        for (key, packet) in self.signature_packets() {
+            assert_return!(
+                !packet.is_empty() && packet.len() <= MAX_PACKET_SIZE,
+                WireguardError::InvalidAmneziaSetting(key.to_string())
+            );
+        }
+
         Ok(())
     }
+
+    /// Get settings with shared values (S1-S4 and H1-H4) of `shared` and local values (Jc,
+    /// Jmin, Jmax and I1-I5) of `self`.
+    ///
+    /// Shared values must be same on both ends of connection, while local values may differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let server = AmneziaSettings::random();
+    /// let client = AmneziaSettings::random().with_shared_values(&server);
+    ///
+    /// assert!(client.shared_values_match(&server));
+    /// ```
+    pub fn with_shared_values(&self, shared: &AmneziaSettings) -> Self {
+        Self {
+            s1: shared.s1,
+            s2: shared.s2,
+            s3: shared.s3,
+            s4: shared.s4,
+            h1: shared.h1,
+            h2: shared.h2,
+            h3: shared.h3,
+            h4: shared.h4,
+            ..self.clone()
+        }
+    }
+
+    /// Checks whether shared values (S1-S4 and H1-H4) are same in both settings.
+    #[must_use]
+    pub fn shared_values_match(&self, other: &AmneziaSettings) -> bool {
+        (self.s1, self.s2, self.s3, self.s4) == (other.s1, other.s2, other.s3, other.s4)
+            && [self.h1, self.h2, self.h3, self.h4] == [other.h1, other.h2, other.h3, other.h4]
+    }
+
+    /// Get set values with their config's keys (`("Jc", "4")`, etc), in config's order.
+    pub(crate) fn values(&self) -> Vec<(&'static str, String)> {
+        let mut values = vec![
+            ("Jc", self.jc.to_string()),
+            ("Jmin", self.jmin.to_string()),
+            ("Jmax", self.jmax.to_string()),
+            ("S1", self.s1.to_string()),
+            ("S2", self.s2.to_string()),
+        ];
+        values.extend(self.s3.map(|s3| ("S3", s3.to_string())));
+        values.extend(self.s4.map(|s4| ("S4", s4.to_string())));
+        values.extend([
+            ("H1", self.h1.to_string()),
+            ("H2", self.h2.to_string()),
+            ("H3", self.h3.to_string()),
+            ("H4", self.h4.to_string()),
+        ]);
+        values.extend(
+            self.signature_packets()
+                .map(|(key, packet)| (key, packet.to_string())),
+        );
+
+        values
+    }
+
+    /// Parses values by config's keys (see [`AmneziaSettings::values()`]), that are got by `get`.
+    ///
+    /// Returns `None`, if there aren't any values.
+    pub(crate) fn from_values<'a>(
+        get: impl Fn(&str) -> Option<&'a str>,
+    ) -> WireguardResult<Option<AmneziaSettings>> {
+        fn parse<'a, T: FromStr>(
+            get: &impl Fn(&str) -> Option<&'a str>,
+            key: &str,
+        ) -> WireguardResult<Option<T>> {
+            get(key)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| WireguardError::InvalidAmneziaSetting(key.to_string()))
+                })
+                .transpose()
+        }
+        fn require<'a, T: FromStr>(
+            get: &impl Fn(&str) -> Option<&'a str>,
+            key: &str,
+        ) -> WireguardResult<T> {
+            parse(get, key)?.ok_or_else(|| WireguardError::InvalidAmneziaSetting(key.to_string()))
+        }
+
+        if KEYS.iter().all(|&key| get(key).is_none()) {
+            return Ok(None);
+        }
+
+        let settings = AmneziaSettings {
+            jc: require(&get, "Jc")?,
+            jmin: require(&get, "Jmin")?,
+            jmax: require(&get, "Jmax")?,
+            s1: require(&get, "S1")?,
+            s2: require(&get, "S2")?,
+            s3: parse(&get, "S3")?,
+            s4: parse(&get, "S4")?,
+            h1: require(&get, "H1")?,
+            h2: require(&get, "H2")?,
+            h3: require(&get, "H3")?,
+            h4: require(&get, "H4")?,
+            i1: parse(&get, "I1")?,
+            i2: parse(&get, "I2")?,
+            i3: parse(&get, "I3")?,
+            i4: parse(&get, "I4")?,
+            i5: parse(&get, "I5")?,
+        };
+        settings.validate()?;
+
+        Ok(Some(settings))
+    }
+
+    /// Get set signature packets with their config's keys (`"I1"`, etc), in config's order.
+    pub(crate) fn signature_packets(
+        &self,
+    ) -> impl Iterator<Item = (&'static str, &SignaturePacket)> {
+        [
+            ("I1", &self.i1),
+            ("I2", &self.i2),
+            ("I3", &self.i3),
+            ("I4", &self.i4),
+            ("I5", &self.i5),
+        ]
+        .into_iter()
+        .filter_map(|(key, packet)| Some((key, packet.as_ref()?)))
+    }
 }
 
 /// Implements [`fmt::Display`] for exporting AmneziaWG values.
@@ -142,16 +585,432 @@ impl AmneziaSettings {
 /// It exports only [`Jc = ..., Jmin = ..., etc`]. To export full interface, use [`Interface::to_string()`].
 impl fmt::Display for AmneziaSettings {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Jc = {}", self.jc)?;
-        writeln!(f, "Jmin = {}", self.jmin)?;
-        writeln!(f, "Jmax = {}", self.jmax)?;
-        writeln!(f, "S1 = {}", self.s1)?;
-        writeln!(f, "S2 = {}", self.s2)?;
-        writeln!(f, "H1 = {}", self.h1)?;
-        writeln!(f, "H2 = {}", self.h2)?;
-        writeln!(f, "H3 = {}", self.h3)?;
-        writeln!(f, "H4 = {}", self.h4)?;
+        for (key, value) in self.values() {
+            writeln!(f, "{key} = {value}")?;
+        }
 
         Ok(())
     }
 }
+
+/// Recommended ranges of randomized values.
+const JC_RANGE: (usize, usize) = (3, 10);
+const JMIN_RANGE: (usize, usize) = (40, 60);
+const JMAX_RANGE: (usize, usize) = (900, 1000);
+const S_RANGE: (usize, usize) = (15, 150);
+const H_RANGE: (usize, usize) = (5, 2_147_483_647);
+
+/// Draws random value from inclusive `range`, until it satisfies `is_valid`.
+///
+/// If range is empty, its lower bound is returned. `is_valid` must reject only a few values of
+/// range, otherwise drawing may take long.
+fn random_value(
+    rng: &mut impl Rng,
+    range: (usize, usize),
+    is_valid: impl Fn(usize) -> bool,
+) -> usize {
+    if range.0 >= range.1 {
+        return range.0;
+    }
+
+    loop {
+        let value = rng.random_range(range.0..=range.1);
+
+        if is_valid(value) {
+            return value;
+        }
+    }
+}
+
+/// Draws random header from [`H_RANGE`], that doesn't overlap with `taken` headers.
+///
+/// Returns `None`, if `taken` headers cover whole range.
+fn random_header(rng: &mut impl Rng, taken: &[MagicHeader]) -> Option<usize> {
+    let mut taken: Vec<MagicHeader> = taken
+        .iter()
+        .filter(|header| header.start <= header.end)
+        .copied()
+        .collect();
+    taken.sort_by_key(|header| header.start);
+
+    // free ranges of `H_RANGE`, that aren't covered by `taken` headers
+    let mut free = Vec::new();
+    let mut start = H_RANGE.0;
+    for header in taken {
+        if header.start > start {
+            free.push((start, (header.start - 1).min(H_RANGE.1)));
+        }
+        start = start.max(header.end.saturating_add(1));
+    }
+    if start <= H_RANGE.1 {
+        free.push((start, H_RANGE.1));
+    }
+    free.retain(|(start, end)| start <= end);
+
+    let total: usize = free.iter().map(|(start, end)| end - start + 1).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut offset = rng.random_range(0..total);
+    for (start, end) in free {
+        let len = end - start + 1;
+        if offset < len {
+            return Some(start + offset);
+        }
+        offset -= len;
+    }
+
+    None
+}
+
+/// Builder, that used for creating [`AmneziaSettings`].
+///
+/// Values, that weren't set, are randomized within recommended ranges (see
+/// [`AmneziaSettings`]'s fields) and with respect to values, that were set. S3, S4 and I1-I5
+/// aren't randomized: they're set only if provided.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let settings = AmneziaSettingsBuilder::new()
+///     .jc(5)
+///     .h1(MagicHeader::new(1000, 2000))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(settings.jc, 5);
+/// assert_eq!(settings.h1, MagicHeader::new(1000, 2000));
+/// assert!((15..=150).contains(&settings.s1));
+/// ```
+#[must_use]
+#[derive(Clone, Debug, Default)]
+pub struct AmneziaSettingsBuilder {
+    jc: Option<usize>,
+    jmin: Option<usize>,
+    jmax: Option<usize>,
+    s1: Option<usize>,
+    s2: Option<usize>,
+    s3: Option<usize>,
+    s4: Option<usize>,
+    h1: Option<MagicHeader>,
+    h2: Option<MagicHeader>,
+    h3: Option<MagicHeader>,
+    h4: Option<MagicHeader>,
+    i1: Option<SignaturePacket>,
+    i2: Option<SignaturePacket>,
+    i3: Option<SignaturePacket>,
+    i4: Option<SignaturePacket>,
+    i5: Option<SignaturePacket>,
+}
+
+impl AmneziaSettingsBuilder {
+    pub fn new() -> AmneziaSettingsBuilder {
+        AmneziaSettingsBuilder::default()
+    }
+
+    /// Sets junk packet count.
+    pub fn jc(mut self, jc: usize) -> Self {
+        self.jc = Some(jc);
+        self
+    }
+
+    /// Sets junk packet minimum size.
+    pub fn jmin(mut self, jmin: usize) -> Self {
+        self.jmin = Some(jmin);
+        self
+    }
+
+    /// Sets junk packet maximum size.
+    pub fn jmax(mut self, jmax: usize) -> Self {
+        self.jmax = Some(jmax);
+        self
+    }
+
+    /// Sets handshake initiation packet junk size.
+    pub fn s1(mut self, s1: usize) -> Self {
+        self.s1 = Some(s1);
+        self
+    }
+
+    /// Sets handshake response packet junk size.
+    pub fn s2(mut self, s2: usize) -> Self {
+        self.s2 = Some(s2);
+        self
+    }
+
+    /// Sets cookie reply packet junk size.
+    pub fn s3(mut self, s3: usize) -> Self {
+        self.s3 = Some(s3);
+        self
+    }
+
+    /// Sets transport packet junk size.
+    pub fn s4(mut self, s4: usize) -> Self {
+        self.s4 = Some(s4);
+        self
+    }
+
+    /// Sets handshake initiation packet magic header.
+    pub fn h1(mut self, h1: MagicHeader) -> Self {
+        self.h1 = Some(h1);
+        self
+    }
+
+    /// Sets handshake response packet magic header.
+    pub fn h2(mut self, h2: MagicHeader) -> Self {
+        self.h2 = Some(h2);
+        self
+    }
+
+    /// Sets cookie reply packet magic header.
+    pub fn h3(mut self, h3: MagicHeader) -> Self {
+        self.h3 = Some(h3);
+        self
+    }
+
+    /// Sets transport packet magic header.
+    pub fn h4(mut self, h4: MagicHeader) -> Self {
+        self.h4 = Some(h4);
+        self
+    }
+
+    /// Sets signature packet I1.
+    pub fn i1(mut self, i1: SignaturePacket) -> Self {
+        self.i1 = Some(i1);
+        self
+    }
+
+    /// Sets signature packet I2.
+    pub fn i2(mut self, i2: SignaturePacket) -> Self {
+        self.i2 = Some(i2);
+        self
+    }
+
+    /// Sets signature packet I3.
+    pub fn i3(mut self, i3: SignaturePacket) -> Self {
+        self.i3 = Some(i3);
+        self
+    }
+
+    /// Sets signature packet I4.
+    pub fn i4(mut self, i4: SignaturePacket) -> Self {
+        self.i4 = Some(i4);
+        self
+    }
+
+    /// Sets signature packet I5.
+    pub fn i5(mut self, i5: SignaturePacket) -> Self {
+        self.i5 = Some(i5);
+        self
+    }
+
+    /// Creates [`AmneziaSettings`], randomizing values, that weren't set.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidAmneziaSetting`] -- set values are invalid
+    ///   (see [`AmneziaSettings::validate()`]), or set headers don't leave space for headers,
+    ///   that weren't set.
+    pub fn build(self) -> WireguardResult<AmneziaSettings> {
+        let settings = self.generate()?;
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    /// Fills values, that weren't set, with random ones.
+    fn generate(self) -> WireguardResult<AmneziaSettings> {
+        let mut rng = rand::rng();
+
+        let jc = self
+            .jc
+            .unwrap_or_else(|| random_value(&mut rng, JC_RANGE, |_| true));
+
+        let jmin = self.jmin.unwrap_or_else(|| {
+            let limit = self.jmax.map_or(usize::MAX, |jmax| jmax.saturating_sub(1));
+
+            random_value(
+                &mut rng,
+                (JMIN_RANGE.0.min(limit), JMIN_RANGE.1.min(limit)),
+                |_| true,
+            )
+        });
+        let jmax = self.jmax.unwrap_or_else(|| {
+            random_value(
+                &mut rng,
+                (
+                    JMAX_RANGE.0.max(jmin.saturating_add(1)),
+                    JMAX_RANGE.1.max(jmin.saturating_add(1)),
+                ),
+                |_| true,
+            )
+        });
+
+        // S3 is only pinned, so S1 and S2 are drawn with respect to it
+        let differs_from_s3 = |size: usize| self.s3.is_none_or(|s3| COOKIE_REPLY_SIZE + s3 != size);
+
+        let s1 = self.s1.unwrap_or_else(|| {
+            random_value(&mut rng, S_RANGE, |s1| {
+                differs_from_s3(INITIATION_SIZE + s1)
+                    && self
+                        .s2
+                        .is_none_or(|s2| INITIATION_SIZE + s1 != RESPONSE_SIZE + s2)
+            })
+        });
+        let s2 = self.s2.unwrap_or_else(|| {
+            random_value(&mut rng, S_RANGE, |s2| {
+                differs_from_s3(RESPONSE_SIZE + s2) && INITIATION_SIZE + s1 != RESPONSE_SIZE + s2
+            })
+        });
+
+        let mut headers = [self.h1, self.h2, self.h3, self.h4];
+        for index in 0..headers.len() {
+            if headers[index].is_some() {
+                continue;
+            }
+
+            let taken: Vec<MagicHeader> = headers.iter().flatten().copied().collect();
+            let value = random_header(&mut rng, &taken)
+                .ok_or_else(|| WireguardError::InvalidAmneziaSetting(format!("H{}", index + 1)))?;
+            headers[index] = Some(value.into());
+        }
+        let [h1, h2, h3, h4] = headers.map(Option::unwrap_or_default);
+
+        Ok(AmneziaSettings {
+            jc,
+            jmin,
+            jmax,
+            s1,
+            s2,
+            s3: self.s3,
+            s4: self.s4,
+            h1,
+            h2,
+            h3,
+            h4,
+            i1: self.i1,
+            i2: self.i2,
+            i3: self.i3,
+            i4: self.i4,
+            i5: self.i5,
+        })
+    }
+}
+
+/// Warning about AmneziaWG compatibility between interface and its peer.
+///
+/// See [`Interface::amnezia_warnings()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AmneziaWarning {
+    /// Interface is obfuscated, but peer doesn't have AmneziaWG values, so it's considered
+    /// vanilla WireGuard peer. Contains peer's name or public key.
+    VanillaPeer(String),
+
+    /// Peer has AmneziaWG values, but interface isn't obfuscated. Contains peer's name or
+    /// public key.
+    ObfuscatedPeer(String),
+}
+
+impl fmt::Display for AmneziaWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmneziaWarning::VanillaPeer(peer) => write!(
+                f,
+                "peer {peer} is vanilla WireGuard, but interface uses AmneziaWG: handshake would fail"
+            ),
+            AmneziaWarning::ObfuscatedPeer(peer) => write!(
+                f,
+                "peer {peer} uses AmneziaWG, but interface is vanilla WireGuard: handshake would fail"
+            ),
+        }
+    }
+}
+
+/// Get peer's name or, if peer doesn't have name, its public key.
+fn peer_label(peer: &Peer) -> String {
+    peer.name
+        .clone()
+        .unwrap_or_else(|| peer.public_key().to_string())
+}
+
+impl Interface {
+    /// Get copy of interface without AmneziaWG values (both interface's and peers'), that can be
+    /// used by stock WireGuard.
+    ///
+    /// # Note
+    ///
+    /// Vanilla WireGuard can't connect to obfuscated AmneziaWG peers, so only use it for peers,
+    /// that are vanilla too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let interface = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .amnezia_settings(AmneziaSettings::random())
+    ///     .build();
+    ///
+    /// let vanilla = interface.to_vanilla();
+    ///
+    /// assert_eq!(vanilla.amnezia_settings, None);
+    /// assert!(!vanilla.to_string().contains("Jc = "));
+    /// ```
+    pub fn to_vanilla(&self) -> Interface {
+        let mut interface = self.clone();
+
+        interface.amnezia_settings = None;
+        for peer in &mut interface.peers {
+            peer.amnezia_settings = None;
+        }
+
+        interface
+    }
+
+    /// Get warnings about peers, that can't complete handshake with this interface, because
+    /// one side uses AmneziaWG and other side doesn't.
+    ///
+    /// # Note
+    ///
+    /// Peers without [`AmneziaSettings`] are considered vanilla. Set peer's
+    /// [`AmneziaSettings`] (e.g. interface's ones) to mark it as AmneziaWG peer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let peer = PeerBuilder::new()
+    ///     .name("Old router".to_string())
+    ///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+    ///     .build();
+    ///
+    /// let interface = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .amnezia_settings(AmneziaSettings::random())
+    ///     .add_peer(peer)
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     interface.amnezia_warnings(),
+    ///     vec![AmneziaWarning::VanillaPeer("Old router".to_string())]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn amnezia_warnings(&self) -> Vec<AmneziaWarning> {
+        self.peers
+            .iter()
+            .filter_map(
+                |peer| match (&self.amnezia_settings, &peer.amnezia_settings) {
+                    (Some(_), None) => Some(AmneziaWarning::VanillaPeer(peer_label(peer))),
+                    (None, Some(_)) => Some(AmneziaWarning::ObfuscatedPeer(peer_label(peer))),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+}
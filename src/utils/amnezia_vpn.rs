@@ -0,0 +1,256 @@
+use base64::prelude::*;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use serde_json::{json, Map, Value};
+
+use std::io::{Read as _, Write as _};
+
+use crate::prelude::*;
+
+/// Prefix of Amnezia VPN links.
+const LINK_PREFIX: &str = "vpn://";
+
+/// Name of AmneziaWG container in Amnezia VPN.
+const CONTAINER: &str = "amnezia-awg";
+
+/// Maximum size of decompressed config in links.
+const MAX_CONFIG_SIZE: u32 = 1 << 20;
+
+/// Compresses data like Qt's `qCompress`: big-endian length of data followed by zlib stream.
+fn q_compress(data: &[u8]) -> WireguardResult<Vec<u8>> {
+    let length =
+        u32::try_from(data.len()).map_err(|_| WireguardError::InvalidValue("JSON".to_string()))?;
+
+    let mut encoder = ZlibEncoder::new(length.to_be_bytes().to_vec(), Compression::best());
+    encoder.write_all(data).expect("writing to Vec can't fail");
+
+    Ok(encoder.finish().expect("writing to Vec can't fail"))
+}
+
+/// Decompresses data, compressed by Qt's `qCompress`.
+///
+/// Returns `None`, if data is malformed, its size doesn't match size in header or is bigger than
+/// [`MAX_CONFIG_SIZE`]. No more than size in header is decompressed.
+fn q_uncompress(data: &[u8]) -> Option<Vec<u8>> {
+    let (length, stream) = data.split_first_chunk::<4>()?;
+    let length = u32::from_be_bytes(*length);
+    if length > MAX_CONFIG_SIZE {
+        return None;
+    }
+
+    let mut decompressed = Vec::with_capacity(length as usize);
+    ZlibDecoder::new(stream)
+        .take(u64::from(length) + 1)
+        .read_to_end(&mut decompressed)
+        .ok()?;
+
+    (decompressed.len() == length as usize).then_some(decompressed)
+}
+
+/// Get `settings` values with Amnezia VPN's keys and string values.
+fn settings_fields(settings: &AmneziaSettings) -> Map<String, Value> {
+    let mut fields = Map::new();
+
+    for (key, value) in settings.values() {
+        fields.insert(key.to_string(), Value::String(value));
+    }
+
+    fields
+}
+
+impl Interface {
+    /// Get Amnezia VPN client config (JSON), that can be imported by Amnezia VPN app.
+    ///
+    /// Interface must be client's interface (see [`Peer::to_interface()`]): its first peer is
+    /// used as server. `description` is shown as server's name in app.
+    ///
+    /// - [Amnezia VPN](https://amnezia.org)
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::MissingAmneziaSettings`] -- interface doesn't have
+    ///   [`AmneziaSettings`].
+    /// - [`WireguardError::MissingSection`] -- interface doesn't have peers.
+    /// - [`WireguardError::InvalidValue`] -- server's endpoint is missing or isn't `host:port`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let server = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .endpoint("vpn.example.com:51820".to_string())
+    ///     .amnezia_settings(AmneziaSettings::random())
+    ///     .build();
+    ///
+    /// let client = PeerBuilder::new()
+    ///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+    ///     .build()
+    ///     .to_interface(&server)
+    ///     .unwrap();
+    ///
+    /// let link = client.to_amnezia_link("My VPN").unwrap();
+    /// assert!(link.starts_with("vpn://"));
+    ///
+    /// let imported = Interface::from_amnezia_link(&link).unwrap();
+    /// assert_eq!(imported.to_string(), client.to_string());
+    /// ```
+    pub fn to_amnezia_json(&self, description: &str) -> WireguardResult<String> {
+        let settings = self
+            .amnezia_settings
+            .as_ref()
+            .ok_or(WireguardError::MissingAmneziaSettings)?;
+        let server = self
+            .peers
+            .first()
+            .ok_or(WireguardError::MissingSection("Peer".to_string()))?;
+        let (host, port) = server
+            .endpoint_parts()?
+            .ok_or(WireguardError::InvalidValue("Endpoint".to_string()))?;
+
+        let mut last_config = settings_fields(settings);
+        last_config.extend([
+            (
+                "allowed_ips".to_string(),
+                server.allowed_ips.iter().map(ToString::to_string).collect(),
+            ),
+            (
+                "client_ip".to_string(),
+                json!(self.address.addr().to_string()),
+            ),
+            (
+                "client_priv_key".to_string(),
+                json!(self.private_key.to_string()),
+            ),
+            (
+                "client_pub_key".to_string(),
+                json!(PublicKey::from(&self.private_key).to_string()),
+            ),
+            ("config".to_string(), json!(self.to_string())),
+            ("hostName".to_string(), json!(host)),
+            ("port".to_string(), json!(port)),
+            (
+                "server_pub_key".to_string(),
+                json!(server.public_key().to_string()),
+            ),
+        ]);
+        if let Some(mtu) = self.mtu {
+            last_config.insert("mtu".to_string(), json!(mtu.to_string()));
+        }
+        if let Some(persistent_keepalive) = server.persistent_keepalive {
+            last_config.insert(
+                "persistent_keep_alive".to_string(),
+                json!(persistent_keepalive.to_string()),
+            );
+        }
+        if let Some(preshared_key) = &server.preshared_key {
+            last_config.insert("psk_key".to_string(), json!(preshared_key.to_string()));
+        }
+
+        let mut container = settings_fields(settings);
+        container.extend([
+            (
+                "last_config".to_string(),
+                json!(Value::Object(last_config).to_string()),
+            ),
+            ("port".to_string(), json!(port.to_string())),
+            ("transport_proto".to_string(), json!("udp")),
+        ]);
+
+        let mut config = json!({
+            "containers": [{
+                "awg": container,
+                "container": CONTAINER,
+            }],
+            "defaultContainer": CONTAINER,
+            "description": description,
+            "hostName": host,
+        });
+        for (key, dns) in ["dns1", "dns2"].into_iter().zip(&self.dns) {
+            config[key] = json!(dns);
+        }
+
+        Ok(config.to_string())
+    }
+
+    /// Get Amnezia VPN link (`vpn://...`) with compressed config from
+    /// [`Interface::to_amnezia_json()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- config is bigger than 4 GiB.
+    /// - Errors of [`Interface::to_amnezia_json()`].
+    pub fn to_amnezia_link(&self, description: &str) -> WireguardResult<String> {
+        let config = self.to_amnezia_json(description)?;
+
+        Ok(format!(
+            "{LINK_PREFIX}{}",
+            BASE64_URL_SAFE_NO_PAD.encode(q_compress(config.as_bytes())?)
+        ))
+    }
+
+    /// Creates [`Interface`] from Amnezia VPN config (JSON).
+    ///
+    /// Config of AmneziaWG container (default container, if there are several) is used.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- JSON is malformed or doesn't have AmneziaWG
+    ///   container with config.
+    /// - Errors of config parsing (see [`ConfigDocument`]'s conversion to [`Interface`]).
+    pub fn from_amnezia_json(json: &str) -> WireguardResult<Interface> {
+        let error = |key: &str| WireguardError::InvalidValue(key.to_string());
+
+        let config: Value = serde_json::from_str(json).map_err(|_| error("JSON"))?;
+        let containers = config["containers"]
+            .as_array()
+            .ok_or_else(|| error("containers"))?;
+        let default_container = config["defaultContainer"].as_str();
+
+        let container = containers
+            .iter()
+            .filter(|container| container["awg"].is_object())
+            .max_by_key(|container| container["container"].as_str() == default_container)
+            .ok_or_else(|| error("awg"))?;
+
+        let last_config: Value = container["awg"]["last_config"]
+            .as_str()
+            .and_then(|last_config| serde_json::from_str(last_config).ok())
+            .ok_or_else(|| error("last_config"))?;
+        let config = last_config["config"]
+            .as_str()
+            .ok_or_else(|| error("config"))?;
+
+        Interface::try_from(ConfigDocument::parse(config)?)
+    }
+
+    /// Creates [`Interface`] from Amnezia VPN link (`vpn://...`).
+    ///
+    /// Both compressed and uncompressed links are supported.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- link is malformed, or its compressed data is
+    ///   corrupt, bigger than its header says or bigger than 1 MiB.
+    /// - Errors of [`Interface::from_amnezia_json()`].
+    pub fn from_amnezia_link(link: &str) -> WireguardResult<Interface> {
+        let error = || WireguardError::InvalidValue("vpn://".to_string());
+
+        let data = link
+            .trim()
+            .strip_prefix(LINK_PREFIX)
+            .ok_or_else(error)?
+            .trim_end_matches('=');
+        let data = BASE64_URL_SAFE_NO_PAD.decode(data).map_err(|_| error())?;
+
+        // uncompressed links contain JSON as is, compressed ones start with data's size
+        let json = if data.trim_ascii_start().starts_with(b"{") {
+            data
+        } else {
+            q_uncompress(&data).ok_or_else(error)?
+        };
+        let json = String::from_utf8(json).map_err(|_| error())?;
+
+        Interface::from_amnezia_json(&json)
+    }
+}
@@ -0,0 +1,612 @@
+use blake2::{
+    digest::{consts::U16, Mac},
+    Blake2s256, Blake2sMac, Digest,
+};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305,
+};
+use hmac::SimpleHmac;
+use rand::{Rng, RngCore};
+use x25519_dalek::{PublicKey as XPublicKey, StaticSecret};
+
+use std::{
+    ops::RangeInclusive,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::prelude::*;
+
+const CONSTRUCTION: &[u8] = b"Noise_IKpsk2_25519_ChaChaPoly_BLAKE2s";
+const IDENTIFIER: &[u8] = b"WireGuard v1 zx2c4 Jason@zx2c4.com";
+const LABEL_MAC1: &[u8] = b"mac1----";
+
+/// Sizes of handshake messages.
+const INITIATION_SIZE: usize = 148;
+const RESPONSE_SIZE: usize = 92;
+
+/// Message types.
+const INITIATION_TYPE: u8 = 1;
+const RESPONSE_TYPE: u8 = 2;
+
+/// TAI64 label of UNIX epoch.
+const TAI64_EPOCH: u64 = 0x4000_0000_0000_000a;
+
+fn failed(reason: &str) -> WireguardError {
+    WireguardError::HandshakeFailed(reason.to_string())
+}
+
+fn blake2s(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Blake2s256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+
+    hasher.finalize().into()
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut hmac = <SimpleHmac<Blake2s256> as Mac>::new_from_slice(key)
+        .expect("HMAC accepts keys of any size");
+    for part in parts {
+        hmac.update(part);
+    }
+
+    hmac.finalize().into_bytes().into()
+}
+
+fn mac(key: &[u8; 32], data: &[u8]) -> [u8; 16] {
+    let mut mac = <Blake2sMac<U16> as Mac>::new_from_slice(key).expect("key must be 32 bytes");
+    mac.update(data);
+
+    mac.finalize().into_bytes().into()
+}
+
+/// HKDF with HMAC-BLAKE2s, that returns `N` keys.
+fn kdf<const N: usize>(chaining_key: &[u8; 32], input: &[u8]) -> [[u8; 32]; N] {
+    let secret = hmac(chaining_key, &[input]);
+    let mut outputs = [[0; 32]; N];
+
+    for index in 0..N {
+        let previous: &[u8] = if index == 0 { &[] } else { &outputs[index - 1] };
+        #[allow(clippy::cast_possible_truncation)]
+        let output = hmac(&secret, &[previous, &[index as u8 + 1]]);
+
+        outputs[index] = output;
+    }
+
+    outputs
+}
+
+fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    ChaCha20Poly1305::new(key.into())
+        .encrypt(
+            &[0; 12].into(),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("message must be small enough")
+}
+
+fn open(key: &[u8; 32], ciphertext: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    ChaCha20Poly1305::new(key.into())
+        .decrypt(
+            &[0; 12].into(),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .ok()
+}
+
+fn dh(private_key: &StaticSecret, public_key: &[u8; 32]) -> [u8; 32] {
+    private_key
+        .diffie_hellman(&XPublicKey::from(*public_key))
+        .to_bytes()
+}
+
+/// Get current TAI64N timestamp.
+fn timestamp() -> [u8; 12] {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let mut timestamp = [0; 12];
+    timestamp[..8].copy_from_slice(&(TAI64_EPOCH + now.as_secs()).to_be_bytes());
+    timestamp[8..].copy_from_slice(&now.subsec_nanos().to_be_bytes());
+
+    timestamp
+}
+
+/// Get initial chaining key and hash of handshake with responder `public_key`.
+fn initial_state(public_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let chaining_key = blake2s(&[CONSTRUCTION]);
+    let hash = blake2s(&[&blake2s(&[&chaining_key, IDENTIFIER]), public_key]);
+
+    (chaining_key, hash)
+}
+
+fn read_index(packet: &[u8], offset: usize) -> u32 {
+    let mut index = [0; 4];
+    index.copy_from_slice(&packet[offset..offset + 4]);
+
+    u32::from_le_bytes(index)
+}
+
+/// Framing of handshake message on the wire: size of random padding before message and range
+/// of header values (first 4 bytes of message).
+///
+/// Vanilla WireGuard doesn't pad messages and uses message type as header. AmneziaWG pads
+/// messages with S1/S2 random bytes and takes headers from H1/H2 ranges.
+#[derive(Clone, Debug)]
+struct Framing {
+    padding: usize,
+    headers: RangeInclusive<u32>,
+}
+
+impl Framing {
+    /// Get framing of messages with `message_type`, used by `interface`.
+    #[cfg_attr(not(feature = "amneziawg"), allow(unused_variables))]
+    fn new(interface: &Interface, message_type: u8) -> Self {
+        #[cfg(feature = "amneziawg")]
+        if let Some(settings) = &interface.amnezia_settings {
+            let (padding, header) = if message_type == INITIATION_TYPE {
+                (settings.s1, settings.h1)
+            } else {
+                (settings.s2, settings.h2)
+            };
+            let to_u32 = |value: usize| u32::try_from(value).unwrap_or(u32::MAX);
+
+            return Self {
+                padding,
+                headers: to_u32(header.start)..=to_u32(header.end),
+            };
+        }
+
+        Self {
+            padding: 0,
+            headers: u32::from(message_type)..=u32::from(message_type),
+        }
+    }
+
+    /// Get random header from allowed range.
+    fn header(&self) -> [u8; 4] {
+        let header = if self.headers.is_empty() {
+            *self.headers.start()
+        } else {
+            rand::rng().random_range(self.headers.clone())
+        };
+
+        header.to_le_bytes()
+    }
+
+    /// Get packet with `message`, prefixed by random padding.
+    fn pad(&self, message: Vec<u8>) -> Vec<u8> {
+        if self.padding == 0 {
+            return message;
+        }
+
+        let mut packet = vec![0; self.padding];
+        rand::rng().fill_bytes(&mut packet);
+        packet.extend(message);
+
+        packet
+    }
+
+    /// Get message of `size` from `packet`, if packet is framed correctly.
+    fn unpad<'p>(&self, packet: &'p [u8], size: usize) -> Option<&'p [u8]> {
+        let message = packet.get(self.padding..)?;
+
+        (message.len() == size && self.headers.contains(&read_index(message, 0))).then_some(message)
+    }
+}
+
+/// Transport keys, derived after successful handshake.
+///
+/// Keys of initiator and responder are mirrored: initiator's `send` key is responder's
+/// `receive` key and vice versa.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionKeys {
+    /// Key for encrypting outgoing transport data.
+    pub send: [u8; 32],
+    /// Key for decrypting incoming transport data.
+    pub receive: [u8; 32],
+}
+
+/// Initiator's side of WireGuard handshake: builds handshake initiation packet and verifies
+/// handshake response.
+///
+/// It's useful for probing servers: send [`HandshakeInitiator::initiation()`] to server's
+/// endpoint over UDP and pass the answer to [`HandshakeInitiator::consume_response()`].
+///
+/// With `amneziawg` feature and interface's [`AmneziaSettings`], packets are obfuscated like
+/// AmneziaWG does: initiation is prefixed with S1 random bytes and has header from H1 range,
+/// response is expected to be prefixed with S2 bytes and have header from H2 range. Send
+/// [`HandshakeInitiator::junk_packets()`] before initiation.
+///
+/// - [WireGuard Protocol](https://www.wireguard.com/protocol/)
+///
+/// # Note
+///
+/// Cookie replies (server under load) aren't supported, MAC2 is always zeroed.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let client = PeerBuilder::new()
+///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+///     .build();
+///
+/// let server = InterfaceBuilder::new()
+///     .address("10.0.0.1/24".parse().unwrap())
+///     .add_peer(client.clone())
+///     .build();
+/// let client = client.to_interface(&server).unwrap();
+///
+/// let mut initiator = HandshakeInitiator::new(&client, &client.peers[0]);
+/// let initiation = initiator.initiation();
+/// assert_eq!(initiation.len(), 148);
+///
+/// // server's side
+/// let responder = HandshakeResponder::new(&server);
+/// let (_, response, server_keys) = responder.respond(&initiation).unwrap();
+///
+/// let client_keys = initiator.consume_response(&response).unwrap();
+/// assert_eq!(client_keys.send, server_keys.receive);
+/// ```
+pub struct HandshakeInitiator {
+    private_key: StaticSecret,
+    public_key: [u8; 32],
+    peer_public_key: [u8; 32],
+    preshared_key: [u8; 32],
+
+    #[cfg(feature = "amneziawg")]
+    amnezia_settings: Option<AmneziaSettings>,
+    initiation_framing: Framing,
+    response_framing: Framing,
+
+    sender_index: u32,
+    ephemeral: StaticSecret,
+    chaining_key: [u8; 32],
+    hash: [u8; 32],
+}
+
+impl HandshakeInitiator {
+    /// Creates initiator of handshake from `interface` with `peer`.
+    #[must_use]
+    pub fn new(interface: &Interface, peer: &Peer) -> Self {
+        let private_key = StaticSecret::from(interface.private_key.to_bytes());
+        let peer_public_key = *peer.public_key().as_bytes();
+        let (chaining_key, hash) = initial_state(&peer_public_key);
+
+        Self {
+            public_key: XPublicKey::from(&private_key).to_bytes(),
+            private_key,
+            peer_public_key,
+            preshared_key: peer
+                .preshared_key
+                .as_ref()
+                .map_or([0; 32], |preshared_key| *preshared_key.as_bytes()),
+
+            #[cfg(feature = "amneziawg")]
+            amnezia_settings: interface.amnezia_settings.clone(),
+            initiation_framing: Framing::new(interface, INITIATION_TYPE),
+            response_framing: Framing::new(interface, RESPONSE_TYPE),
+
+            sender_index: rand::rng().next_u32(),
+            ephemeral: StaticSecret::random(),
+            chaining_key,
+            hash,
+        }
+    }
+
+    /// Get sender index of the latest initiation. Server's response refers to it.
+    #[must_use]
+    pub fn sender_index(&self) -> u32 {
+        self.sender_index
+    }
+
+    /// Get AmneziaWG packets, that must be sent before initiation: signature packets (I1-I5)
+    /// followed by Jc junk packets of random size between Jmin and Jmax.
+    ///
+    /// Returns empty [`Vec`], if interface doesn't have [`AmneziaSettings`].
+    #[cfg(feature = "amneziawg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "amneziawg")))]
+    #[must_use]
+    pub fn junk_packets(&self) -> Vec<Vec<u8>> {
+        let Some(settings) = &self.amnezia_settings else {
+            return Vec::new();
+        };
+        let mut rng = rand::rng();
+
+        let mut packets: Vec<Vec<u8>> = settings
+            .signature_packets()
+            .zip(1..)
+            .map(|((_, packet), counter)| packet.to_bytes(counter))
+            .collect();
+
+        for _ in 0..settings.jc {
+            let size = rng.random_range(settings.jmin..=settings.jmax.max(settings.jmin));
+            let mut packet = vec![0; size];
+            rng.fill_bytes(&mut packet);
+
+            packets.push(packet);
+        }
+
+        packets
+    }
+
+    /// Get handshake initiation packet.
+    ///
+    /// Every call starts handshake over with new ephemeral key and sender index, so only the
+    /// latest initiation's response can be consumed.
+    pub fn initiation(&mut self) -> Vec<u8> {
+        // reusing ephemeral key would reuse timestamp's AEAD key and nonce
+        self.ephemeral = StaticSecret::random();
+        self.sender_index = rand::rng().next_u32();
+
+        let ephemeral_public = XPublicKey::from(&self.ephemeral).to_bytes();
+        let (chaining_key, hash) = initial_state(&self.peer_public_key);
+
+        let [chaining_key] = kdf(&chaining_key, &ephemeral_public);
+        let hash = blake2s(&[&hash, &ephemeral_public]);
+
+        let [chaining_key, key] = kdf(&chaining_key, &dh(&self.ephemeral, &self.peer_public_key));
+        let encrypted_static = seal(&key, &self.public_key, &hash);
+        let hash = blake2s(&[&hash, &encrypted_static]);
+
+        let [chaining_key, key] = kdf(&chaining_key, &dh(&self.private_key, &self.peer_public_key));
+        let encrypted_timestamp = seal(&key, &timestamp(), &hash);
+        let hash = blake2s(&[&hash, &encrypted_timestamp]);
+
+        self.chaining_key = chaining_key;
+        self.hash = hash;
+
+        let mut packet = Vec::with_capacity(INITIATION_SIZE);
+        packet.extend(self.initiation_framing.header());
+        packet.extend(self.sender_index.to_le_bytes());
+        packet.extend(ephemeral_public);
+        packet.extend(encrypted_static);
+        packet.extend(encrypted_timestamp);
+
+        let mac1 = mac(&blake2s(&[LABEL_MAC1, &self.peer_public_key]), &packet);
+        packet.extend(mac1);
+        packet.extend([0; 16]);
+
+        self.initiation_framing.pad(packet)
+    }
+
+    /// Consumes handshake response packet, returning transport keys.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::HandshakeFailed`] -- response is malformed (or framed with other
+    ///   AmneziaWG values) or can't be verified.
+    pub fn consume_response(&self, packet: &[u8]) -> WireguardResult<SessionKeys> {
+        let packet = self
+            .response_framing
+            .unpad(packet, RESPONSE_SIZE)
+            .ok_or_else(|| failed("malformed handshake response"))?;
+        if read_index(packet, 8) != self.sender_index {
+            return Err(failed("response's receiver index doesn't match initiation"));
+        }
+        if packet[60..76] != mac(&blake2s(&[LABEL_MAC1, &self.public_key]), &packet[..60]) {
+            return Err(failed(
+                "response's MAC1 is invalid: server doesn't know client's public key",
+            ));
+        }
+
+        let mut ephemeral_public = [0; 32];
+        ephemeral_public.copy_from_slice(&packet[12..44]);
+
+        let [chaining_key] = kdf(&self.chaining_key, &ephemeral_public);
+        let hash = blake2s(&[&self.hash, &ephemeral_public]);
+        let [chaining_key] = kdf(&chaining_key, &dh(&self.ephemeral, &ephemeral_public));
+        let [chaining_key] = kdf(&chaining_key, &dh(&self.private_key, &ephemeral_public));
+
+        let [chaining_key, tau, key] = kdf(&chaining_key, &self.preshared_key);
+        let hash = blake2s(&[&hash, &tau]);
+        open(&key, &packet[44..60], &hash)
+            .ok_or_else(|| failed("response can't be decrypted: preshared keys differ"))?;
+
+        let [send, receive] = kdf(&chaining_key, &[]);
+
+        Ok(SessionKeys { send, receive })
+    }
+}
+
+/// Responder's side of WireGuard handshake: verifies handshake initiation packets and builds
+/// responses, like server does.
+///
+/// With `amneziawg` feature and interface's [`AmneziaSettings`], packets are framed like
+/// AmneziaWG does (see [`HandshakeInitiator`]). Junk and signature packets are rejected as
+/// malformed initiations and should be skipped.
+pub struct HandshakeResponder<'a> {
+    interface: &'a Interface,
+    private_key: StaticSecret,
+    public_key: [u8; 32],
+
+    initiation_framing: Framing,
+    response_framing: Framing,
+}
+
+impl<'a> HandshakeResponder<'a> {
+    /// Creates responder of handshakes to `interface`.
+    #[must_use]
+    pub fn new(interface: &'a Interface) -> Self {
+        let private_key = StaticSecret::from(interface.private_key.to_bytes());
+
+        Self {
+            interface,
+            public_key: XPublicKey::from(&private_key).to_bytes(),
+            private_key,
+
+            initiation_framing: Framing::new(interface, INITIATION_TYPE),
+            response_framing: Framing::new(interface, RESPONSE_TYPE),
+        }
+    }
+
+    /// Consumes handshake initiation packet, returning initiator peer, response packet and
+    /// transport keys.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::HandshakeFailed`] -- initiation is malformed (or framed with other
+    ///   AmneziaWG values), can't be verified or initiator isn't interface's peer.
+    pub fn respond(&self, packet: &[u8]) -> WireguardResult<(&'a Peer, Vec<u8>, SessionKeys)> {
+        let packet = self
+            .initiation_framing
+            .unpad(packet, INITIATION_SIZE)
+            .ok_or_else(|| failed("malformed handshake initiation"))?;
+        if packet[116..132] != mac(&blake2s(&[LABEL_MAC1, &self.public_key]), &packet[..116]) {
+            return Err(failed(
+                "initiation's MAC1 is invalid: client's peer public key doesn't match server",
+            ));
+        }
+        let sender_index = read_index(packet, 4);
+
+        let mut initiator_ephemeral = [0; 32];
+        initiator_ephemeral.copy_from_slice(&packet[8..40]);
+
+        let (chaining_key, hash) = initial_state(&self.public_key);
+        let [chaining_key] = kdf(&chaining_key, &initiator_ephemeral);
+        let hash = blake2s(&[&hash, &initiator_ephemeral]);
+
+        let [chaining_key, key] = kdf(&chaining_key, &dh(&self.private_key, &initiator_ephemeral));
+        let initiator_static: [u8; 32] = open(&key, &packet[40..88], &hash)
+            .and_then(|public_key| public_key.try_into().ok())
+            .ok_or_else(|| failed("initiation's static key can't be decrypted"))?;
+        let hash = blake2s(&[&hash, &packet[40..88]]);
+
+        let peer = self
+            .interface
+            .peers
+            .iter()
+            .find(|peer| peer.public_key().as_bytes() == &initiator_static)
+            .ok_or_else(|| failed("server doesn't have client's public key as peer"))?;
+
+        let [chaining_key, key] = kdf(&chaining_key, &dh(&self.private_key, &initiator_static));
+        open(&key, &packet[88..116], &hash)
+            .ok_or_else(|| failed("initiation's timestamp can't be decrypted"))?;
+        let hash = blake2s(&[&hash, &packet[88..116]]);
+
+        let ephemeral = StaticSecret::random();
+        let ephemeral_public = XPublicKey::from(&ephemeral).to_bytes();
+
+        let [chaining_key] = kdf(&chaining_key, &ephemeral_public);
+        let hash = blake2s(&[&hash, &ephemeral_public]);
+        let [chaining_key] = kdf(&chaining_key, &dh(&ephemeral, &initiator_ephemeral));
+        let [chaining_key] = kdf(&chaining_key, &dh(&ephemeral, &initiator_static));
+
+        let preshared_key = peer
+            .preshared_key
+            .as_ref()
+            .map_or([0; 32], |preshared_key| *preshared_key.as_bytes());
+        let [chaining_key, tau, key] = kdf(&chaining_key, &preshared_key);
+        let hash = blake2s(&[&hash, &tau]);
+        let encrypted_nothing = seal(&key, &[], &hash);
+
+        let mut response = Vec::with_capacity(RESPONSE_SIZE);
+        response.extend(self.response_framing.header());
+        response.extend(rand::rng().next_u32().to_le_bytes());
+        response.extend(sender_index.to_le_bytes());
+        response.extend(ephemeral_public);
+        response.extend(encrypted_nothing);
+
+        let mac1 = mac(&blake2s(&[LABEL_MAC1, &initiator_static]), &response);
+        response.extend(mac1);
+        response.extend([0; 16]);
+
+        let [receive, send] = kdf(&chaining_key, &[]);
+
+        Ok((
+            peer,
+            self.response_framing.pad(response),
+            SessionKeys { send, receive },
+        ))
+    }
+}
+
+impl Interface {
+    /// Performs WireGuard handshake (Noise_IKpsk2) between this server's interface and
+    /// `client`'s interface in memory, checking whether they would successfully pair.
+    ///
+    /// Client's peer with server's public key is used as server. If there's no such peer,
+    /// client's first peer is used.
+    ///
+    /// - [WireGuard Protocol](https://www.wireguard.com/protocol/)
+    ///
+    /// # Note
+    ///
+    /// Only keys are checked: endpoints, allowed IPs and cookies aren't part of this test.
+    /// With `amneziawg` feature, AmneziaWG values, that must match on both ends, are compared
+    /// too.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::HandshakeFailed`] -- handshake would fail. Error contains reason:
+    ///   client's peer public key doesn't match server's private key, server doesn't know
+    ///   client's public key, preshared keys differ, etc.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let client = PeerBuilder::new()
+    ///     .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+    ///     .preshared_key(PresharedKey::random())
+    ///     .build();
+    ///
+    /// let server = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .add_peer(client.clone())
+    ///     .build();
+    ///
+    /// let mut client = client.to_interface(&server).unwrap();
+    /// client.peers[0].preshared_key = server.peers[0].preshared_key.clone();
+    ///
+    /// assert_eq!(server.test_handshake(&client), Ok(()));
+    ///
+    /// // preshared key is missing on client's side
+    /// client.peers[0].preshared_key = None;
+    /// assert!(server.test_handshake(&client).is_err());
+    /// ```
+    pub fn test_handshake(&self, client: &Interface) -> WireguardResult<()> {
+        let server_public_key = PublicKey::from(&self.private_key);
+        let server_peer = client
+            .peers
+            .iter()
+            .find(|peer| peer.public_key() == server_public_key)
+            .or_else(|| client.peers.first())
+            .ok_or_else(|| failed("client doesn't have peers"))?;
+
+        #[cfg(feature = "amneziawg")]
+        {
+            let matches = match (&self.amnezia_settings, &client.amnezia_settings) {
+                (Some(server), Some(client)) => server.shared_values_match(client),
+                (server, client) => server.is_none() && client.is_none(),
+            };
+            if !matches {
+                return Err(failed("AmneziaWG values differ"));
+            }
+        }
+
+        let mut initiator = HandshakeInitiator::new(client, server_peer);
+        let responder = HandshakeResponder::new(self);
+
+        let (_, response, server_keys) = responder.respond(&initiator.initiation())?;
+        let client_keys = initiator.consume_response(&response)?;
+
+        if client_keys.send != server_keys.receive || client_keys.receive != server_keys.send {
+            return Err(failed("transport keys differ"));
+        }
+
+        Ok(())
+    }
+}
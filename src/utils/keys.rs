@@ -23,6 +23,20 @@ impl PrivateKey {
             secret: StaticSecret::random(),
         }
     }
+
+    /// Returns the raw bytes of the private key.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.secret.to_bytes()
+    }
+}
+
+impl From<[u8; 32]> for PrivateKey {
+    fn from(value: [u8; 32]) -> Self {
+        Self {
+            secret: StaticSecret::from(value),
+        }
+    }
 }
 
 impl fmt::Debug for PrivateKey {
@@ -70,6 +84,22 @@ pub struct PublicKey {
     key: XPublicKey,
 }
 
+impl PublicKey {
+    /// Returns a reference to the raw bytes of the public key.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        self.key.as_bytes()
+    }
+}
+
+impl From<[u8; 32]> for PublicKey {
+    fn from(value: [u8; 32]) -> Self {
+        Self {
+            key: XPublicKey::from(value),
+        }
+    }
+}
+
 impl fmt::Debug for PublicKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PublicKey")
@@ -135,6 +165,12 @@ impl PresharedKey {
     }
 }
 
+impl From<[u8; 32]> for PresharedKey {
+    fn from(value: [u8; 32]) -> Self {
+        Self { key: value }
+    }
+}
+
 impl fmt::Debug for PresharedKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PresharedKey")
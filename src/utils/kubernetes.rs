@@ -0,0 +1,231 @@
+use base64::prelude::*;
+
+use std::fmt::Write as _;
+
+use crate::prelude::*;
+
+/// Converts `value` to DNS subdomain name (lowercase alphanumerics, `-` and `.`), that is
+/// required for Kubernetes objects' names.
+fn object_name(value: &str) -> String {
+    let name: String = value
+        .chars()
+        .map(|char| match char.to_ascii_lowercase() {
+            char @ ('a'..='z' | '0'..='9' | '.' | '-') => char,
+            _ => '-',
+        })
+        .collect();
+
+    name.trim_matches(|char| char == '-' || char == '.')
+        .to_string()
+}
+
+/// Converts `value` to data key (alphanumerics, `-`, `_` and `.`).
+fn data_key(value: &str) -> String {
+    value
+        .chars()
+        .map(|char| match char {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => char,
+            _ => '-',
+        })
+        .collect()
+}
+
+/// Quotes YAML string.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            char => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// Removes private keys and preshared keys from config.
+fn strip_secrets(config: &str) -> WireguardResult<String> {
+    let mut document = ConfigDocument::parse(config)?;
+
+    for section in document.sections_mut() {
+        section.remove("PrivateKey");
+        section.remove("PresharedKey");
+    }
+
+    Ok(document.to_string())
+}
+
+/// Get peer's public key, that can be used as data key.
+fn public_key_name(peer: &Peer) -> String {
+    peer.public_key()
+        .to_string()
+        .replace('+', "-")
+        .replace('/', "_")
+        .replace('=', "")
+}
+
+/// Kubernetes `v1/Secret` and `v1/ConfigMap` manifests with WireGuard configs.
+///
+/// Every config is stored as separate data entry (e.g. `wg0.conf`), so it can be mounted as
+/// file. Names are derived only from given names and peers' names (or public keys), so
+/// manifests are stable between runs.
+///
+/// [`KubernetesManifest::config_map()`] doesn't contain private and preshared keys, so it's
+/// safe to use for non-secret parts of configs.
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// let interface = InterfaceBuilder::new()
+///     .address("10.0.0.1/24".parse().unwrap())
+///     .build();
+///
+/// let secret = KubernetesManifest::new("wireguard")
+///     .namespace("vpn")
+///     .add_interface("wg0", &interface)
+///     .secret();
+///
+/// assert!(secret.starts_with("apiVersion: v1\nkind: Secret\n"));
+/// assert!(secret.contains("\n  wg0.conf: "));
+/// ```
+#[must_use]
+#[derive(Clone, Debug, Default)]
+pub struct KubernetesManifest {
+    name: String,
+    namespace: Option<String>,
+    labels: Vec<(String, String)>,
+    configs: Vec<(String, String)>,
+}
+
+impl KubernetesManifest {
+    /// Creates empty manifest. `name` is converted to valid object's name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: object_name(name),
+            ..Default::default()
+        }
+    }
+
+    /// Sets object's namespace.
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(object_name(namespace));
+        self
+    }
+
+    /// Adds object's label.
+    pub fn label(mut self, key: String, value: String) -> Self {
+        self.labels.push((key, value));
+        self
+    }
+
+    /// Adds `interface`'s config as `<name>.conf` entry.
+    ///
+    /// If entry with same name exists, it's replaced.
+    pub fn add_interface(mut self, name: &str, interface: &Interface) -> Self {
+        let key = format!("{}.conf", data_key(name));
+
+        self.configs.retain(|(k, _)| *k != key);
+        self.configs.push((key, interface.to_string()));
+        self
+    }
+
+    /// Adds clients' configs (see [`Peer::to_interface()`]) for every `server`'s peer.
+    ///
+    /// Entry is named after peer's name or, if peer doesn't have name or its name is already
+    /// used by other peer, after its public key.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- `server` has same peer twice.
+    /// - Errors of [`Peer::to_interface()`].
+    pub fn add_clients(mut self, server: &Interface) -> WireguardResult<Self> {
+        let mut used_keys: Vec<String> = Vec::new();
+
+        for peer in &server.peers {
+            let name = peer
+                .name
+                .as_deref()
+                .filter(|name| !used_keys.contains(&data_key(name)))
+                .map_or_else(|| public_key_name(peer), ToString::to_string);
+
+            let key = data_key(&name);
+            if used_keys.contains(&key) {
+                return Err(WireguardError::InvalidValue(format!(
+                    "duplicate peer {}",
+                    peer.public_key()
+                )));
+            }
+            used_keys.push(key);
+
+            self = self.add_interface(&name, &peer.to_interface(server)?);
+        }
+
+        Ok(self)
+    }
+
+    fn write_metadata(&self, manifest: &mut String, kind: &str) {
+        let _ = writeln!(manifest, "apiVersion: v1");
+        let _ = writeln!(manifest, "kind: {kind}");
+        let _ = writeln!(manifest, "metadata:");
+        let _ = writeln!(manifest, "  name: {}", quote(&self.name));
+        if let Some(namespace) = &self.namespace {
+            let _ = writeln!(manifest, "  namespace: {}", quote(namespace));
+        }
+        if !self.labels.is_empty() {
+            let _ = writeln!(manifest, "  labels:");
+            for (key, value) in &self.labels {
+                let _ = writeln!(manifest, "    {}: {}", quote(key), quote(value));
+            }
+        }
+    }
+
+    /// Get `v1/Secret` manifest with base64-encoded configs.
+    #[must_use]
+    pub fn secret(&self) -> String {
+        let mut manifest = String::new();
+
+        self.write_metadata(&mut manifest, "Secret");
+        let _ = writeln!(manifest, "type: Opaque");
+        let _ = writeln!(manifest, "data:");
+        for (key, config) in &self.configs {
+            let _ = writeln!(manifest, "  {key}: {}", BASE64_STANDARD.encode(config));
+        }
+
+        manifest
+    }
+
+    /// Get `v1/ConfigMap` manifest with configs, from which private and preshared keys are
+    /// removed.
+    ///
+    /// # Errors
+    ///
+    /// - Errors of [`ConfigDocument::parse()`] -- config can't be parsed for removing keys.
+    pub fn config_map(&self) -> WireguardResult<String> {
+        let mut manifest = String::new();
+
+        self.write_metadata(&mut manifest, "ConfigMap");
+        let _ = writeln!(manifest, "data:");
+        for (key, config) in &self.configs {
+            let config = strip_secrets(config)?;
+            let chomping = if config.ends_with('\n') { '+' } else { '-' };
+
+            let _ = writeln!(manifest, "  {key}: |{chomping}");
+            for line in config.lines() {
+                if line.is_empty() {
+                    manifest.push('\n');
+                } else {
+                    let _ = writeln!(manifest, "    {line}");
+                }
+            }
+        }
+
+        Ok(manifest)
+    }
+}
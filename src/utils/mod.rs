@@ -1,13 +1,40 @@
 #[cfg(feature = "amneziawg")]
 mod amnezia;
+#[cfg(feature = "amneziavpn")]
+mod amnezia_vpn;
+#[cfg(feature = "handshake")]
+mod handshake;
 mod keys;
+#[cfg(feature = "kubernetes")]
+mod kubernetes;
+#[cfg(all(feature = "netlink", target_os = "linux"))]
+mod netlink;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "uapi")]
+mod uapi;
 
 use thiserror::Error;
 
 #[cfg(feature = "amneziawg")]
 #[cfg_attr(docsrs, doc(cfg(feature = "amneziawg")))]
 pub use amnezia::*;
+#[cfg(feature = "handshake")]
+#[cfg_attr(docsrs, doc(cfg(feature = "handshake")))]
+pub use handshake::*;
 pub use keys::*;
+#[cfg(feature = "kubernetes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "kubernetes")))]
+pub use kubernetes::*;
+#[cfg(all(feature = "netlink", target_os = "linux"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "netlink", target_os = "linux"))))]
+pub use netlink::*;
+#[cfg(feature = "prometheus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+pub use prometheus::*;
+#[cfg(feature = "uapi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uapi")))]
+pub use uapi::*;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum WireguardError {
@@ -26,9 +53,56 @@ pub enum WireguardError {
     #[error("no assigned ip")]
     NoAssignedIP,
 
+    #[error("no address provided")]
+    NoAddressProvided,
+
+    #[error("no allowed ips")]
+    NoAllowedIPs,
+
+    #[error("invalid listen port")]
+    InvalidListenPort,
+
+    #[error("duplicate dns: {0}")]
+    DuplicateDns(String),
+
+    #[error("no public key provided")]
+    NoPublicKeyProvided,
+
+    #[error("invalid line {0}")]
+    InvalidLine(usize),
+
+    #[error("invalid value: {0}")]
+    InvalidValue(String),
+
+    #[error("missing section: [{0}]")]
+    MissingSection(String),
+
+    #[error("io error: {0}")]
+    Io(String),
+
     #[cfg(feature = "amneziawg")]
     #[error("invalid amnezia setting: {0}")]
     InvalidAmneziaSetting(String),
+
+    #[cfg(feature = "amneziawg")]
+    #[error("peer's amnezia settings don't match interface's: {0}")]
+    AmneziaMismatch(String),
+
+    #[cfg(feature = "amneziawg")]
+    #[error("no amnezia settings provided")]
+    MissingAmneziaSettings,
+
+    #[cfg(feature = "handshake")]
+    #[error("handshake failed: {0}")]
+    HandshakeFailed(String),
+
+    #[cfg(feature = "uapi")]
+    #[error("uapi error: errno {0}")]
+    UapiErrno(i32),
+
+    #[cfg(all(feature = "netlink", target_os = "linux"))]
+    #[error("netlink error: errno {0}")]
+    Netlink(i32),
 }
 
 pub type WireguardResult<T> = Result<T, WireguardError>;
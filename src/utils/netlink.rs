@@ -0,0 +1,776 @@
+use ipnet::Ipv4Net;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use crate::prelude::*;
+
+const WG_GENL_NAME: &str = "wireguard";
+const WG_GENL_VERSION: u8 = 1;
+
+const WG_CMD_GET_DEVICE: u8 = 0;
+const WG_CMD_SET_DEVICE: u8 = 1;
+
+const WGDEVICE_A_IFNAME: u16 = 2;
+const WGDEVICE_A_PRIVATE_KEY: u16 = 3;
+const WGDEVICE_A_FLAGS: u16 = 5;
+const WGDEVICE_A_LISTEN_PORT: u16 = 6;
+const WGDEVICE_A_FWMARK: u16 = 7;
+const WGDEVICE_A_PEERS: u16 = 8;
+
+const WGDEVICE_F_REPLACE_PEERS: u32 = 1;
+
+const WGPEER_A_PUBLIC_KEY: u16 = 1;
+const WGPEER_A_PRESHARED_KEY: u16 = 2;
+const WGPEER_A_FLAGS: u16 = 3;
+const WGPEER_A_ENDPOINT: u16 = 4;
+const WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL: u16 = 5;
+const WGPEER_A_ALLOWEDIPS: u16 = 9;
+
+const WGPEER_F_REPLACE_ALLOWEDIPS: u32 = 2;
+
+const WGALLOWEDIP_A_FAMILY: u16 = 1;
+const WGALLOWEDIP_A_IPADDR: u16 = 2;
+const WGALLOWEDIP_A_CIDR_MASK: u16 = 3;
+
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+/// Types below are reserved for netlink's control messages.
+const NLMSG_MIN_TYPE: u16 = 0x10;
+
+const NLM_F_REQUEST: u16 = 1;
+const NLM_F_ACK: u16 = 4;
+const NLM_F_DUMP: u16 = 0x300;
+
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+const AF_NETLINK: u16 = 16;
+
+const NLA_F_NESTED: u16 = 0x8000;
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+/// `nlmsghdr` + `genlmsghdr`.
+const HEADER_LEN: usize = 16 + 4;
+
+/// Messages are kept under this size, like `wg` does.
+const MAX_MESSAGE_LEN: usize = 8192;
+
+/// Allowed IPs per peer's chunk. Peers with more allowed IPs are split between messages.
+const ALLOWED_IPS_PER_CHUNK: usize = 128;
+
+const RECEIVE_BUFFER_LEN: usize = 64 * 1024;
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn invalid_message() -> WireguardError {
+    WireguardError::InvalidValue("netlink message".to_string())
+}
+
+/// Generic netlink message builder.
+struct Message {
+    buffer: Vec<u8>,
+}
+
+impl Message {
+    fn new(kind: u16, flags: u16, seq: u32, cmd: u8, version: u8) -> Self {
+        let mut buffer = Vec::with_capacity(MAX_MESSAGE_LEN);
+        buffer.extend(0u32.to_ne_bytes());
+        buffer.extend(kind.to_ne_bytes());
+        buffer.extend(flags.to_ne_bytes());
+        buffer.extend(seq.to_ne_bytes());
+        buffer.extend(0u32.to_ne_bytes());
+        buffer.extend([cmd, version, 0, 0]);
+
+        Self { buffer }
+    }
+
+    fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn put(&mut self, kind: u16, payload: &[u8]) -> WireguardResult<()> {
+        let start = self.begin(kind);
+        self.buffer.extend(payload);
+        self.end(start)
+    }
+
+    fn put_u16(&mut self, kind: u16, value: u16) -> WireguardResult<()> {
+        self.put(kind, &value.to_ne_bytes())
+    }
+
+    fn put_u32(&mut self, kind: u16, value: u32) -> WireguardResult<()> {
+        self.put(kind, &value.to_ne_bytes())
+    }
+
+    /// Starts attribute, returns its offset for [`Message::end()`].
+    fn begin(&mut self, kind: u16) -> usize {
+        let start = self.buffer.len();
+        self.buffer.extend(0u16.to_ne_bytes());
+        self.buffer.extend(kind.to_ne_bytes());
+        start
+    }
+
+    fn begin_nested(&mut self, kind: u16) -> usize {
+        self.begin(kind | NLA_F_NESTED)
+    }
+
+    /// Ends attribute, that was started at `start`.
+    ///
+    /// Fails, if attribute doesn't fit into `u16` length.
+    fn end(&mut self, start: usize) -> WireguardResult<()> {
+        let len = u16::try_from(self.buffer.len() - start).map_err(|_| invalid_message())?;
+        self.buffer[start..start + 2].copy_from_slice(&len.to_ne_bytes());
+        self.buffer.resize(align(self.buffer.len()), 0);
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let len = u32::try_from(self.buffer.len()).unwrap_or(u32::MAX);
+        self.buffer[0..4].copy_from_slice(&len.to_ne_bytes());
+        self.buffer
+    }
+}
+
+/// Iterates over netlink attributes: `(type, payload)`.
+fn attributes(mut data: &[u8]) -> impl Iterator<Item = WireguardResult<(u16, &[u8])>> {
+    std::iter::from_fn(move || {
+        if data.len() < 4 {
+            return None;
+        }
+
+        let len = usize::from(u16::from_ne_bytes([data[0], data[1]]));
+        let kind = u16::from_ne_bytes([data[2], data[3]]) & NLA_TYPE_MASK;
+        if len < 4 || len > data.len() {
+            data = &[];
+            return Some(Err(invalid_message()));
+        }
+
+        let payload = &data[4..len];
+        data = &data[align(len).min(data.len())..];
+
+        Some(Ok((kind, payload)))
+    })
+}
+
+/// Iterates over netlink messages: `(type, message)`.
+fn messages(mut data: &[u8]) -> impl Iterator<Item = WireguardResult<(u16, &[u8])>> {
+    std::iter::from_fn(move || {
+        if data.len() < 16 {
+            return None;
+        }
+
+        let len = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let kind = u16::from_ne_bytes([data[4], data[5]]);
+        if len < 16 || len > data.len() {
+            data = &[];
+            return Some(Err(invalid_message()));
+        }
+
+        let message = &data[..len];
+        data = &data[align(len).min(data.len())..];
+
+        Some(Ok((kind, message)))
+    })
+}
+
+fn read_key(payload: &[u8]) -> WireguardResult<[u8; 32]> {
+    payload.try_into().map_err(|_| invalid_message())
+}
+
+fn read_u16(payload: &[u8]) -> WireguardResult<u16> {
+    Ok(u16::from_ne_bytes(
+        payload.try_into().map_err(|_| invalid_message())?,
+    ))
+}
+
+fn read_u32(payload: &[u8]) -> WireguardResult<u32> {
+    Ok(u32::from_ne_bytes(
+        payload.try_into().map_err(|_| invalid_message())?,
+    ))
+}
+
+/// Encodes endpoint as `sockaddr_in`/`sockaddr_in6`.
+fn encode_endpoint(endpoint: SocketAddr) -> Vec<u8> {
+    let mut sockaddr = Vec::with_capacity(28);
+
+    match endpoint {
+        SocketAddr::V4(endpoint) => {
+            sockaddr.extend(AF_INET.to_ne_bytes());
+            sockaddr.extend(endpoint.port().to_be_bytes());
+            sockaddr.extend(endpoint.ip().octets());
+            sockaddr.extend([0; 8]);
+        }
+        SocketAddr::V6(endpoint) => {
+            sockaddr.extend(AF_INET6.to_ne_bytes());
+            sockaddr.extend(endpoint.port().to_be_bytes());
+            sockaddr.extend(endpoint.flowinfo().to_be_bytes());
+            sockaddr.extend(endpoint.ip().octets());
+            sockaddr.extend(endpoint.scope_id().to_ne_bytes());
+        }
+    }
+
+    sockaddr
+}
+
+/// Decodes `sockaddr_in`/`sockaddr_in6`.
+fn decode_endpoint(payload: &[u8]) -> WireguardResult<Option<SocketAddr>> {
+    if payload.len() < 4 {
+        return Err(invalid_message());
+    }
+
+    let family = u16::from_ne_bytes([payload[0], payload[1]]);
+    let port = u16::from_be_bytes([payload[2], payload[3]]);
+
+    match family {
+        AF_INET if payload.len() >= 8 => {
+            let ip: [u8; 4] = payload[4..8].try_into().map_err(|_| invalid_message())?;
+            Ok(Some(SocketAddr::from((ip, port))))
+        }
+        AF_INET6 if payload.len() >= 28 => {
+            let ip: [u8; 16] = payload[8..24].try_into().map_err(|_| invalid_message())?;
+            let scope_id = read_u32(&payload[24..28])?;
+            Ok(Some(SocketAddr::V6(std::net::SocketAddrV6::new(
+                ip.into(),
+                port,
+                0,
+                scope_id,
+            ))))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Encodes peer's chunk as `WGDEVICE_A_PEERS` entry. The first chunk carries peer's config,
+/// following ones only append allowed IPs.
+fn encode_peer(
+    peer: &Peer,
+    endpoint: Option<SocketAddr>,
+    allowed_ips: &[Ipv4Net],
+    is_first: bool,
+) -> WireguardResult<Vec<u8>> {
+    let mut message = Message { buffer: Vec::new() };
+
+    let peer_start = message.begin_nested(0);
+    message.put(WGPEER_A_PUBLIC_KEY, peer.public_key().as_bytes())?;
+
+    if is_first {
+        message.put_u32(WGPEER_A_FLAGS, WGPEER_F_REPLACE_ALLOWEDIPS)?;
+        if let Some(preshared_key) = &peer.preshared_key {
+            message.put(WGPEER_A_PRESHARED_KEY, preshared_key.as_bytes())?;
+        }
+        if let Some(endpoint) = endpoint {
+            message.put(WGPEER_A_ENDPOINT, &encode_endpoint(endpoint))?;
+        }
+        if let Some(persistent_keepalive) = peer.persistent_keepalive {
+            let persistent_keepalive = u16::try_from(persistent_keepalive)
+                .map_err(|_| WireguardError::InvalidValue("PersistentKeepalive".to_string()))?;
+
+            message.put_u16(WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL, persistent_keepalive)?;
+        }
+    }
+
+    let allowed_ips_start = message.begin_nested(WGPEER_A_ALLOWEDIPS);
+    for allowed_ip in allowed_ips {
+        let entry_start = message.begin_nested(0);
+        message.put_u16(WGALLOWEDIP_A_FAMILY, AF_INET)?;
+        message.put(WGALLOWEDIP_A_IPADDR, &allowed_ip.addr().octets())?;
+        message.put(WGALLOWEDIP_A_CIDR_MASK, &[allowed_ip.prefix_len()])?;
+        message.end(entry_start)?;
+    }
+    message.end(allowed_ips_start)?;
+
+    message.end(peer_start)?;
+
+    Ok(message.buffer)
+}
+
+/// Decodes `WGDEVICE_A_PEERS` entry.
+fn decode_peer(payload: &[u8]) -> WireguardResult<Peer> {
+    let mut builder = PeerBuilder::new();
+    let mut has_public_key = false;
+
+    for attribute in attributes(payload) {
+        let (kind, payload) = attribute?;
+
+        match kind {
+            WGPEER_A_PUBLIC_KEY => {
+                builder = builder.public_key(PublicKey::from(read_key(payload)?));
+                has_public_key = true;
+            }
+            WGPEER_A_PRESHARED_KEY => {
+                let bytes = read_key(payload)?;
+                if bytes != [0; 32] {
+                    builder = builder.preshared_key(PresharedKey::from(bytes));
+                }
+            }
+            WGPEER_A_ENDPOINT => {
+                if let Some(endpoint) = decode_endpoint(payload)? {
+                    builder = builder.endpoint(endpoint.to_string());
+                }
+            }
+            WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL => {
+                let persistent_keepalive = read_u16(payload)?;
+                if persistent_keepalive != 0 {
+                    builder = builder.persistent_keepalive(u32::from(persistent_keepalive));
+                }
+            }
+            WGPEER_A_ALLOWEDIPS => {
+                for allowed_ip in attributes(payload) {
+                    if let Some(allowed_ip) = decode_allowed_ip(allowed_ip?.1)? {
+                        builder = builder.add_allowed_ip(allowed_ip);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !has_public_key {
+        return Err(WireguardError::NoPublicKeyProvided);
+    }
+
+    Ok(builder.build())
+}
+
+/// Decodes allowed IP. IPv6 networks are skipped.
+fn decode_allowed_ip(payload: &[u8]) -> WireguardResult<Option<Ipv4Net>> {
+    let mut family = None;
+    let mut address = None;
+    let mut prefix_len = None;
+
+    for attribute in attributes(payload) {
+        let (kind, payload) = attribute?;
+
+        match kind {
+            WGALLOWEDIP_A_FAMILY => family = Some(read_u16(payload)?),
+            WGALLOWEDIP_A_IPADDR => address = Some(payload),
+            WGALLOWEDIP_A_CIDR_MASK => prefix_len = payload.first().copied(),
+            _ => {}
+        }
+    }
+
+    if family != Some(AF_INET) {
+        return Ok(None);
+    }
+
+    let (Some(address), Some(prefix_len)) = (address, prefix_len) else {
+        return Err(invalid_message());
+    };
+    let address: [u8; 4] = address.try_into().map_err(|_| invalid_message())?;
+
+    Ipv4Net::new(Ipv4Addr::from(address), prefix_len)
+        .map(Some)
+        .map_err(|_| invalid_message())
+}
+
+fn io_error() -> WireguardError {
+    WireguardError::Io(std::io::Error::last_os_error().to_string())
+}
+
+/// Client of WireGuard's generic netlink API (Linux kernel module).
+///
+/// Requires `CAP_NET_ADMIN`. Interface itself must be already created (e.g. with
+/// `ip link add wg0 type wireguard`).
+///
+/// # Examples
+///
+/// ```no_run
+/// use wireguard_conf::prelude::*;
+///
+/// let interface = InterfaceBuilder::new()
+///     .address("10.0.0.1/24".parse().unwrap())
+///     .listen_port(51820)
+///     .build();
+///
+/// let mut netlink = WireguardNetlink::connect().unwrap();
+/// netlink.apply("wg0", &interface).unwrap();
+///
+/// let current = netlink.get("wg0").unwrap();
+/// assert_eq!(current.listen_port, Some(51820));
+/// ```
+#[derive(Debug)]
+pub struct WireguardNetlink {
+    socket: OwnedFd,
+    family_id: u16,
+    seq: u32,
+}
+
+impl WireguardNetlink {
+    /// Opens generic netlink socket and resolves WireGuard's family.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::Io`] -- socket can't be opened.
+    /// - [`WireguardError::Netlink`] -- family can't be resolved (e.g. `ENOENT`, if WireGuard's
+    ///   module isn't loaded).
+    pub fn connect() -> WireguardResult<Self> {
+        // SAFETY: plain syscall, returned descriptor is checked before use.
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            )
+        };
+        if fd < 0 {
+            return Err(io_error());
+        }
+        // SAFETY: `fd` is valid and isn't owned by anything else.
+        let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: `sockaddr_nl` is plain data, zeroed value is valid.
+        let mut address: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        address.nl_family = AF_NETLINK;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let address_len = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+
+        // SAFETY: `address` is valid `sockaddr_nl` with correct length.
+        let result = unsafe {
+            libc::bind(
+                socket.as_raw_fd(),
+                std::ptr::addr_of!(address).cast(),
+                address_len,
+            )
+        };
+        if result < 0 {
+            return Err(io_error());
+        }
+
+        let mut netlink = Self {
+            socket,
+            family_id: 0,
+            seq: 0,
+        };
+        netlink.family_id = netlink.resolve_family()?;
+
+        Ok(netlink)
+    }
+
+    /// Replaces device's config with `interface`: private key, listen port, fwmark and peers
+    /// (with preshared keys, endpoints, keepalives and allowed IPs).
+    ///
+    /// Address, DNS, MTU and interface's endpoint aren't part of WireGuard's device, so they're
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::Io`] -- socket error.
+    /// - [`WireguardError::Netlink`] -- kernel rejected config (e.g. `ENODEV`, if interface
+    ///   doesn't exist).
+    /// - Errors of [`WireguardNetlink::set_device_messages()`].
+    pub fn apply(&mut self, interface_name: &str, interface: &Interface) -> WireguardResult<()> {
+        let seq = self.next_seq();
+        let messages = Self::set_device_messages(self.family_id, seq, interface_name, interface)?;
+
+        for message in messages {
+            self.send(&message)?;
+            self.receive()?;
+        }
+
+        Ok(())
+    }
+
+    /// Get device's current config.
+    ///
+    /// Device doesn't know interface's address, so it's `0.0.0.0/0`.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::Io`] -- socket error.
+    /// - [`WireguardError::Netlink`] -- kernel returned error (e.g. `ENODEV`, if interface
+    ///   doesn't exist).
+    /// - Errors of [`WireguardNetlink::parse_device()`].
+    pub fn get(&mut self, interface_name: &str) -> WireguardResult<Interface> {
+        let seq = self.next_seq();
+        self.send(&Self::get_device_message(
+            self.family_id,
+            seq,
+            interface_name,
+        )?)?;
+
+        Self::parse_device(&self.receive()?)
+    }
+
+    /// Encodes `WG_CMD_SET_DEVICE` messages, that replace device's config with `interface`.
+    ///
+    /// Peers are split between several messages, so each message fits into `wg`'s buffer size.
+    /// Only the first message sets device's values and replaces peers.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- peer's endpoint can't be resolved or persistent
+    ///   keepalive is bigger than `65535`.
+    pub fn set_device_messages(
+        family_id: u16,
+        seq: u32,
+        interface_name: &str,
+        interface: &Interface,
+    ) -> WireguardResult<Vec<Vec<u8>>> {
+        let new_message = || {
+            let mut message = Message::new(
+                family_id,
+                NLM_F_REQUEST | NLM_F_ACK,
+                seq,
+                WG_CMD_SET_DEVICE,
+                WG_GENL_VERSION,
+            );
+            message.put(WGDEVICE_A_IFNAME, &nul_terminated(interface_name))?;
+
+            Ok::<_, WireguardError>(message)
+        };
+
+        let mut message = new_message()?;
+        message.put(WGDEVICE_A_PRIVATE_KEY, &interface.private_key.to_bytes())?;
+        if let Some(listen_port) = interface.listen_port {
+            message.put_u16(WGDEVICE_A_LISTEN_PORT, listen_port)?;
+        }
+        if let Some(fwmark) = interface.fwmark {
+            message.put_u32(WGDEVICE_A_FWMARK, fwmark)?;
+        }
+        message.put_u32(WGDEVICE_A_FLAGS, WGDEVICE_F_REPLACE_PEERS)?;
+
+        let mut messages = Vec::new();
+        let mut peers_start = message.begin_nested(WGDEVICE_A_PEERS);
+
+        for peer in &interface.peers {
+            let endpoint = peer.resolve_endpoint()?;
+
+            let mut chunks = peer.allowed_ips.chunks(ALLOWED_IPS_PER_CHUNK);
+            let first_chunk = chunks.next().unwrap_or_default();
+
+            let encoded = std::iter::once(encode_peer(peer, endpoint, first_chunk, true))
+                .chain(chunks.map(|chunk| encode_peer(peer, endpoint, chunk, false)));
+
+            for encoded in encoded {
+                let encoded = encoded?;
+
+                if message.len() + encoded.len() > MAX_MESSAGE_LEN {
+                    message.end(peers_start)?;
+                    messages.push(message.finish());
+
+                    message = new_message()?;
+                    peers_start = message.begin_nested(WGDEVICE_A_PEERS);
+                }
+
+                message.buffer.extend(encoded);
+            }
+        }
+
+        message.end(peers_start)?;
+        messages.push(message.finish());
+
+        Ok(messages)
+    }
+
+    /// Encodes `WG_CMD_GET_DEVICE` dump request.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- interface's name is too long for netlink attribute.
+    pub fn get_device_message(
+        family_id: u16,
+        seq: u32,
+        interface_name: &str,
+    ) -> WireguardResult<Vec<u8>> {
+        let mut message = Message::new(
+            family_id,
+            NLM_F_REQUEST | NLM_F_DUMP,
+            seq,
+            WG_CMD_GET_DEVICE,
+            WG_GENL_VERSION,
+        );
+        message.put(WGDEVICE_A_IFNAME, &nul_terminated(interface_name))?;
+
+        Ok(message.finish())
+    }
+
+    /// Decodes device's messages (responses to `WG_CMD_GET_DEVICE` or `WG_CMD_SET_DEVICE`
+    /// requests) into [`Interface`].
+    ///
+    /// Peers, that are split between messages, are merged. Netlink's control messages are
+    /// skipped. IPv6 allowed IPs are skipped, because they aren't supported yet.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- message is malformed.
+    /// - [`WireguardError::NoPrivateKeyProvided`] -- device doesn't have private key.
+    /// - [`WireguardError::NoPublicKeyProvided`] -- peer doesn't have public key.
+    pub fn parse_device(messages: &[Vec<u8>]) -> WireguardResult<Interface> {
+        let mut builder = InterfaceBuilder::new();
+        let mut has_private_key = false;
+        let mut peers: Vec<Peer> = Vec::new();
+
+        for message in messages {
+            for entry in self::messages(message) {
+                let (kind, message) = entry?;
+                if kind < NLMSG_MIN_TYPE {
+                    continue;
+                }
+                if message.len() < HEADER_LEN {
+                    return Err(invalid_message());
+                }
+
+                for attribute in attributes(&message[HEADER_LEN..]) {
+                    let (kind, payload) = attribute?;
+
+                    match kind {
+                        WGDEVICE_A_PRIVATE_KEY => {
+                            builder = builder.private_key(PrivateKey::from(read_key(payload)?));
+                            has_private_key = true;
+                        }
+                        WGDEVICE_A_LISTEN_PORT => {
+                            let listen_port = read_u16(payload)?;
+                            if listen_port != 0 {
+                                builder = builder.listen_port(listen_port);
+                            }
+                        }
+                        WGDEVICE_A_FWMARK => {
+                            let fwmark = read_u32(payload)?;
+                            if fwmark != 0 {
+                                builder = builder.fwmark(fwmark);
+                            }
+                        }
+                        WGDEVICE_A_PEERS => {
+                            for peer in attributes(payload) {
+                                let peer = decode_peer(peer?.1)?;
+
+                                match peers.last_mut() {
+                                    Some(last) if last.public_key() == peer.public_key() => {
+                                        last.allowed_ips.extend(peer.allowed_ips);
+                                    }
+                                    _ => peers.push(peer),
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // otherwise builder would generate random key, that device doesn't have
+        if !has_private_key {
+            return Err(WireguardError::NoPrivateKeyProvided);
+        }
+
+        Ok(builder.set_peers(peers).build())
+    }
+
+    fn next_seq(&mut self) -> u32 {
+        self.seq = self.seq.wrapping_add(1);
+        self.seq
+    }
+
+    fn resolve_family(&mut self) -> WireguardResult<u16> {
+        let seq = self.next_seq();
+        let mut message = Message::new(GENL_ID_CTRL, NLM_F_REQUEST, seq, CTRL_CMD_GETFAMILY, 1);
+        message.put(CTRL_ATTR_FAMILY_NAME, &nul_terminated(WG_GENL_NAME))?;
+        self.send(&message.finish())?;
+
+        for message in self.receive_messages(false)? {
+            for entry in messages(&message) {
+                let (_, message) = entry?;
+                let Some(payload) = message.get(HEADER_LEN..) else {
+                    continue;
+                };
+
+                for attribute in attributes(payload) {
+                    let (kind, payload) = attribute?;
+                    if kind == CTRL_ATTR_FAMILY_ID {
+                        return read_u16(payload);
+                    }
+                }
+            }
+        }
+
+        Err(invalid_message())
+    }
+
+    fn send(&self, message: &[u8]) -> WireguardResult<()> {
+        // SAFETY: `message` is valid for `message.len()` bytes.
+        let sent = unsafe {
+            libc::send(
+                self.socket.as_raw_fd(),
+                message.as_ptr().cast(),
+                message.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(io_error());
+        }
+
+        Ok(())
+    }
+
+    /// Receives messages until acknowledgement or end of dump.
+    fn receive(&self) -> WireguardResult<Vec<Vec<u8>>> {
+        self.receive_messages(true)
+    }
+
+    /// Receives messages until acknowledgement, end of dump or (if `wait_ack` is `false`) first
+    /// data message.
+    fn receive_messages(&self, wait_ack: bool) -> WireguardResult<Vec<Vec<u8>>> {
+        let mut buffer = vec![0u8; RECEIVE_BUFFER_LEN];
+        let mut received = Vec::new();
+
+        loop {
+            // SAFETY: `buffer` is valid for `buffer.len()` bytes.
+            let len = unsafe {
+                libc::recv(
+                    self.socket.as_raw_fd(),
+                    buffer.as_mut_ptr().cast(),
+                    buffer.len(),
+                    0,
+                )
+            };
+            let len = usize::try_from(len).map_err(|_| io_error())?;
+            if len == 0 {
+                return Err(WireguardError::Io(
+                    "netlink socket closed before acknowledgement".to_string(),
+                ));
+            }
+
+            for entry in messages(&buffer[..len]) {
+                let (kind, message) = entry?;
+
+                match kind {
+                    NLMSG_ERROR => {
+                        let errno = message
+                            .get(16..20)
+                            .map(|errno| i32::from_ne_bytes(errno.try_into().unwrap_or_default()))
+                            .ok_or_else(invalid_message)?;
+
+                        if errno != 0 {
+                            return Err(WireguardError::Netlink(-errno));
+                        }
+                        return Ok(received);
+                    }
+                    NLMSG_DONE => return Ok(received),
+                    kind if kind < NLMSG_MIN_TYPE => {}
+                    _ => {
+                        received.push(message.to_vec());
+                        if !wait_ack {
+                            return Ok(received);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn nul_terminated(value: &str) -> Vec<u8> {
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.push(0);
+    bytes
+}
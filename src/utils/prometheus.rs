@@ -0,0 +1,188 @@
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Default [`PrometheusMetrics::handshake_threshold()`]: WireGuard drops session keys, that are
+/// older than 3 minutes.
+const DEFAULT_HANDSHAKE_THRESHOLD: Duration = Duration::from_secs(3 * 60);
+
+/// Per-peer metrics in Prometheus text exposition format.
+///
+/// Metrics are labelled with interface's name, peer's public key, name and allowed IPs:
+///
+/// - `wireguard_peer_up` -- `1` if the latest handshake is newer than threshold, `0` otherwise.
+/// - `wireguard_peer_last_handshake_age_seconds` -- seconds since the latest handshake.
+/// - `wireguard_peer_receive_bytes_total` -- received bytes.
+/// - `wireguard_peer_transmit_bytes_total` -- transmitted bytes.
+///
+/// Peers, that aren't found in status, are reported only as down. Peers, that never had a
+/// handshake, don't have `wireguard_peer_last_handshake_age_seconds`.
+///
+/// - [Exposition format](https://prometheus.io/docs/instrumenting/exposition_formats/)
+///
+/// # Examples
+///
+/// ```
+/// use wireguard_conf::prelude::*;
+///
+/// # let interface = InterfaceBuilder::new().build();
+/// # let status: InterfaceStatus = "(none)\t(none)\t0\toff\n".parse().unwrap();
+/// // let interface: Interface = ...;
+/// // let status: InterfaceStatus = ...;
+///
+/// let metrics = PrometheusMetrics::new(&interface, &status)
+///     .interface_name("wg0".to_string())
+///     .to_string();
+/// ```
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct PrometheusMetrics<'a> {
+    interface: &'a Interface,
+    status: &'a InterfaceStatus,
+    interface_name: Option<String>,
+    handshake_threshold: Duration,
+    now: Option<SystemTime>,
+}
+
+impl<'a> PrometheusMetrics<'a> {
+    pub fn new(interface: &'a Interface, status: &'a InterfaceStatus) -> Self {
+        Self {
+            interface,
+            status,
+            interface_name: None,
+            handshake_threshold: DEFAULT_HANDSHAKE_THRESHOLD,
+            now: None,
+        }
+    }
+
+    /// Sets `interface` label.
+    pub fn interface_name(mut self, interface_name: String) -> Self {
+        self.interface_name = Some(interface_name);
+        self
+    }
+
+    /// Sets maximum age of the latest handshake, for which peer is considered up.
+    ///
+    /// Default is 3 minutes.
+    pub fn handshake_threshold(mut self, handshake_threshold: Duration) -> Self {
+        self.handshake_threshold = handshake_threshold;
+        self
+    }
+
+    /// Sets current time, which is used for calculating handshakes' ages.
+    ///
+    /// Default is [`SystemTime::now()`] at the moment of formatting.
+    pub fn now(mut self, now: SystemTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    fn write_labels(&self, f: &mut fmt::Formatter<'_>, peer: &Peer) -> fmt::Result {
+        write!(f, "{{")?;
+        if let Some(interface_name) = &self.interface_name {
+            write!(f, "interface=\"{}\",", escape(interface_name))?;
+        }
+
+        let allowed_ips = peer
+            .allowed_ips
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join(",");
+
+        write!(
+            f,
+            "public_key=\"{}\",name=\"{}\",allowed_ips=\"{allowed_ips}\"}}",
+            peer.public_key(),
+            escape(peer.name.as_deref().unwrap_or_default()),
+        )
+    }
+}
+
+/// Escapes label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_header(f: &mut fmt::Formatter<'_>, name: &str, kind: &str, help: &str) -> fmt::Result {
+    writeln!(f, "# HELP {name} {help}")?;
+    writeln!(f, "# TYPE {name} {kind}")
+}
+
+/// Implements [`fmt::Display`] for exporting metrics.
+impl fmt::Display for PrometheusMetrics<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let now = self.now.unwrap_or_else(SystemTime::now);
+        let peers = self.interface.peers_status(self.status);
+
+        let handshake_age = |status: &PeerStatus| {
+            status
+                .latest_handshake
+                .map(|handshake| now.duration_since(handshake).unwrap_or_default())
+        };
+
+        write_header(
+            f,
+            "wireguard_peer_up",
+            "gauge",
+            "Whether the latest handshake is newer than threshold.",
+        )?;
+        for (peer, status) in &peers {
+            let is_up = status
+                .and_then(handshake_age)
+                .is_some_and(|age| age <= self.handshake_threshold);
+
+            write!(f, "wireguard_peer_up")?;
+            self.write_labels(f, peer)?;
+            writeln!(f, " {}", u8::from(is_up))?;
+        }
+
+        write_header(
+            f,
+            "wireguard_peer_last_handshake_age_seconds",
+            "gauge",
+            "Seconds since the latest handshake.",
+        )?;
+        for (peer, status) in &peers {
+            if let Some(age) = status.and_then(handshake_age) {
+                write!(f, "wireguard_peer_last_handshake_age_seconds")?;
+                self.write_labels(f, peer)?;
+                writeln!(f, " {}", age.as_secs())?;
+            }
+        }
+
+        write_header(
+            f,
+            "wireguard_peer_receive_bytes_total",
+            "counter",
+            "Bytes received from the peer.",
+        )?;
+        for (peer, status) in &peers {
+            if let Some(status) = status {
+                write!(f, "wireguard_peer_receive_bytes_total")?;
+                self.write_labels(f, peer)?;
+                writeln!(f, " {}", status.transfer_rx)?;
+            }
+        }
+
+        write_header(
+            f,
+            "wireguard_peer_transmit_bytes_total",
+            "counter",
+            "Bytes transmitted to the peer.",
+        )?;
+        for (peer, status) in &peers {
+            if let Some(status) = status {
+                write!(f, "wireguard_peer_transmit_bytes_total")?;
+                self.write_labels(f, peer)?;
+                writeln!(f, " {}", status.transfer_tx)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,441 @@
+use ipnet::Ipv4Net;
+
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+
+use crate::models::parse_value;
+use crate::prelude::*;
+
+/// Get path of interface's UAPI socket (`/var/run/wireguard/<interface>.sock`).
+#[must_use]
+pub fn uapi_socket_path(interface: &str) -> String {
+    format!("/var/run/wireguard/{interface}.sock")
+}
+
+fn to_hex(bytes: &[u8; 32]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+fn from_hex(hex: &str, key: &str) -> WireguardResult<[u8; 32]> {
+    let error = || WireguardError::InvalidValue(key.to_string());
+
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(error());
+    }
+
+    let mut bytes = [0u8; 32];
+    for (byte, chunk) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let chunk = std::str::from_utf8(chunk).map_err(|_| error())?;
+        *byte = u8::from_str_radix(chunk, 16).map_err(|_| error())?;
+    }
+
+    Ok(bytes)
+}
+
+/// Peer's state, returned by UAPI `get=1` operation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UapiPeer {
+    /// Peer's public key.
+    pub public_key: PublicKey,
+
+    /// Peer's preshared key.
+    pub preshared_key: Option<PresharedKey>,
+
+    /// Peer's current endpoint.
+    pub endpoint: Option<SocketAddr>,
+
+    /// Peer's keepalive interval.
+    pub persistent_keepalive_interval: Option<u32>,
+
+    /// Time of the last successful handshake.
+    pub last_handshake: Option<SystemTime>,
+
+    /// Received bytes.
+    pub rx_bytes: u64,
+
+    /// Transmitted bytes.
+    pub tx_bytes: u64,
+
+    /// Peer's allowed IPs.
+    ///
+    /// IPv6 networks are skipped, because they aren't supported yet.
+    pub allowed_ips: Vec<Ipv4Net>,
+}
+
+impl UapiPeer {
+    fn new(public_key: PublicKey) -> Self {
+        Self {
+            public_key,
+            preshared_key: None,
+            endpoint: None,
+            persistent_keepalive_interval: None,
+            last_handshake: None,
+            rx_bytes: 0,
+            tx_bytes: 0,
+            allowed_ips: Vec::new(),
+        }
+    }
+}
+
+/// Device's state, returned by UAPI `get=1` operation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UapiDevice {
+    /// Device's private key.
+    pub private_key: Option<PrivateKey>,
+
+    /// Device's listen port.
+    pub listen_port: Option<u16>,
+
+    /// Device's firewall mark.
+    pub fwmark: Option<u32>,
+
+    /// Device's AmneziaWG obfuscation values (amneziawg-go).
+    #[cfg(feature = "amneziawg")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "amneziawg")))]
+    pub amnezia_settings: Option<AmneziaSettings>,
+
+    /// Device's peers.
+    pub peers: Vec<UapiPeer>,
+}
+
+impl UapiDevice {
+    /// Parses response of UAPI `get=1` operation.
+    ///
+    /// Parsing stops at first empty line. Unknown keys are ignored.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::UapiErrno`] -- response has non-zero `errno`.
+    /// - [`WireguardError::InvalidValue`] -- value can't be parsed.
+    /// - [`WireguardError::InvalidLine`] -- line isn't `key=value`.
+    /// - [`WireguardError::InvalidAmneziaSetting`] -- AmneziaWG value (`jc`, `s1`, `h1`, etc.)
+    ///   is missing, can't be parsed or is invalid (see [`AmneziaSettings::validate()`]).
+    pub fn parse(response: &str) -> WireguardResult<UapiDevice> {
+        let mut device = UapiDevice::default();
+        let mut handshake_seconds = 0;
+        #[cfg(feature = "amneziawg")]
+        let mut amnezia_values = Vec::new();
+
+        for (index, line) in response.lines().enumerate() {
+            if line.is_empty() {
+                break;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(WireguardError::InvalidLine(index + 1))?;
+
+            if key == "public_key" {
+                let public_key = PublicKey::from(from_hex(value, key)?);
+                device.peers.push(UapiPeer::new(public_key));
+                // per-peer state starts over for every peer
+                handshake_seconds = 0;
+                continue;
+            }
+
+            let peer = device.peers.last_mut();
+            match (key, peer) {
+                ("errno", _) => {
+                    let errno = parse_value(value, key)?;
+                    if errno != 0 {
+                        return Err(WireguardError::UapiErrno(errno));
+                    }
+                }
+                ("private_key", None) => {
+                    device.private_key = Some(PrivateKey::from(from_hex(value, key)?));
+                }
+                ("listen_port", None) => device.listen_port = Some(parse_value(value, key)?),
+                ("fwmark", None) => {
+                    device.fwmark = Some(parse_value(value, key)?).filter(|&fwmark| fwmark != 0);
+                }
+                ("preshared_key", Some(peer)) => {
+                    let bytes = from_hex(value, key)?;
+                    peer.preshared_key = (bytes != [0; 32]).then(|| PresharedKey::from(bytes));
+                }
+                ("endpoint", Some(peer)) => peer.endpoint = Some(parse_value(value, key)?),
+                ("persistent_keepalive_interval", Some(peer)) => {
+                    peer.persistent_keepalive_interval =
+                        Some(parse_value(value, key)?).filter(|&interval| interval != 0);
+                }
+                ("last_handshake_time_sec", Some(_)) => {
+                    handshake_seconds = parse_value(value, key)?;
+                }
+                ("last_handshake_time_nsec", Some(peer)) => {
+                    let nanoseconds: u32 = parse_value(value, key)?;
+                    if nanoseconds >= 1_000_000_000 {
+                        return Err(WireguardError::InvalidValue(key.to_string()));
+                    }
+
+                    peer.last_handshake = if handshake_seconds != 0 || nanoseconds != 0 {
+                        let handshake = SystemTime::UNIX_EPOCH
+                            .checked_add(Duration::new(handshake_seconds, nanoseconds))
+                            .ok_or(WireguardError::InvalidValue(
+                                "last_handshake_time_sec".to_string(),
+                            ))?;
+
+                        Some(handshake)
+                    } else {
+                        None
+                    };
+                }
+                ("rx_bytes", Some(peer)) => peer.rx_bytes = parse_value(value, key)?,
+                ("tx_bytes", Some(peer)) => peer.tx_bytes = parse_value(value, key)?,
+                ("allowed_ip", Some(peer)) if !value.contains(':') => {
+                    peer.allowed_ips.push(parse_value(value, key)?);
+                }
+                #[cfg(feature = "amneziawg")]
+                (key, None) => amnezia_values.push((key, value)),
+                _ => {}
+            }
+        }
+
+        #[cfg(feature = "amneziawg")]
+        {
+            device.amnezia_settings = AmneziaSettings::from_values(|key| {
+                amnezia_values
+                    .iter()
+                    .find(|(uapi_key, _)| uapi_key.eq_ignore_ascii_case(key))
+                    .map(|&(_, value)| value)
+            })?;
+        }
+
+        Ok(device)
+    }
+}
+
+/// Converts UAPI device's state to runtime status (like in `wg show <interface> dump`).
+impl From<UapiDevice> for InterfaceStatus {
+    fn from(device: UapiDevice) -> Self {
+        InterfaceStatus {
+            public_key: device.private_key.as_ref().map(PublicKey::from),
+            private_key: device.private_key,
+            listen_port: device.listen_port.unwrap_or(0),
+            fwmark: device.fwmark,
+            peers: device
+                .peers
+                .into_iter()
+                .map(|peer| PeerStatus {
+                    public_key: peer.public_key,
+                    preshared_key: peer.preshared_key,
+                    endpoint: peer.endpoint,
+                    allowed_ips: peer.allowed_ips,
+                    latest_handshake: peer.last_handshake,
+                    transfer_rx: peer.rx_bytes,
+                    transfer_tx: peer.tx_bytes,
+                    persistent_keepalive: peer.persistent_keepalive_interval,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Interface {
+    /// Get UAPI `set=1` operation, that replaces device's config with this interface.
+    ///
+    /// Result ends with empty line, so it can be written to UAPI socket as is.
+    ///
+    /// # Note
+    ///
+    /// Unset fwmark is written as `fwmark=0`, which clears device's fwmark. Unset listen port
+    /// isn't written, so device keeps its current port (`listen_port=0` would pick random one).
+    ///
+    /// UAPI accepts only IP endpoints, so peers' endpoints must be `ip:port`. Hostnames can be
+    /// resolved with [`Peer::resolve_endpoint()`]; [`UapiClient::set()`] does it itself.
+    ///
+    /// AmneziaWG values are written as lowercase keys (`jc=`, `s1=`, `h1=`, etc.), that are
+    /// supported by amneziawg-go only.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::InvalidValue`] -- peer's endpoint isn't `ip:port`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wireguard_conf::prelude::*;
+    ///
+    /// let interface = InterfaceBuilder::new()
+    ///     .address("10.0.0.1/24".parse().unwrap())
+    ///     .listen_port(51820)
+    ///     .build();
+    ///
+    /// let operation = interface.to_uapi().unwrap();
+    ///
+    /// assert!(operation.starts_with("set=1\nprivate_key="));
+    /// assert!(operation.ends_with("listen_port=51820\nfwmark=0\nreplace_peers=true\n\n"));
+    /// ```
+    pub fn to_uapi(&self) -> WireguardResult<String> {
+        let mut operation = String::from("set=1\n");
+
+        let _ = writeln!(
+            operation,
+            "private_key={}",
+            to_hex(&self.private_key.to_bytes())
+        );
+        if let Some(listen_port) = self.listen_port {
+            let _ = writeln!(operation, "listen_port={listen_port}");
+        }
+        let _ = writeln!(operation, "fwmark={}", self.fwmark.unwrap_or(0));
+        #[cfg(feature = "amneziawg")]
+        if let Some(amnezia_settings) = &self.amnezia_settings {
+            for (key, value) in amnezia_settings.values() {
+                let _ = writeln!(operation, "{}={value}", key.to_ascii_lowercase());
+            }
+        }
+        operation.push_str("replace_peers=true\n");
+
+        for peer in &self.peers {
+            let _ = writeln!(
+                operation,
+                "public_key={}",
+                to_hex(peer.public_key().as_bytes())
+            );
+            if let Some(preshared_key) = &peer.preshared_key {
+                let _ = writeln!(
+                    operation,
+                    "preshared_key={}",
+                    to_hex(preshared_key.as_bytes())
+                );
+            }
+            if let Some(endpoint) = &peer.endpoint {
+                let endpoint: SocketAddr = endpoint
+                    .parse()
+                    .map_err(|_| WireguardError::InvalidValue("Endpoint".to_string()))?;
+
+                let _ = writeln!(operation, "endpoint={endpoint}");
+            }
+            if let Some(persistent_keepalive) = peer.persistent_keepalive {
+                let _ = writeln!(
+                    operation,
+                    "persistent_keepalive_interval={persistent_keepalive}"
+                );
+            }
+            operation.push_str("replace_allowed_ips=true\n");
+            for allowed_ip in &peer.allowed_ips {
+                let _ = writeln!(operation, "allowed_ip={allowed_ip}");
+            }
+        }
+
+        operation.push('\n');
+
+        Ok(operation)
+    }
+}
+
+/// Client of WireGuard's cross-platform userspace API (UAPI).
+///
+/// UAPI is implemented by userspace WireGuard implementations (wireguard-go, boringtun, etc.).
+///
+/// - [Documentation](https://www.wireguard.com/xplatform/)
+///
+/// # Examples
+///
+/// ```no_run
+/// use wireguard_conf::prelude::*;
+///
+/// let mut client = UapiClient::connect(&uapi_socket_path("wg0")).unwrap();
+///
+/// let device = client.get().unwrap();
+/// for peer in device.peers {
+///     println!("{}: {} bytes received", peer.public_key, peer.rx_bytes);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct UapiClient<S> {
+    /// Stream is read through single buffer for the whole connection, so bytes buffered after
+    /// response's end aren't lost.
+    stream: BufReader<S>,
+}
+
+#[cfg(unix)]
+impl UapiClient<std::os::unix::net::UnixStream> {
+    /// Connects to UAPI unix socket at `path`.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::Io`] -- socket can't be connected.
+    pub fn connect(path: &str) -> WireguardResult<Self> {
+        let stream = std::os::unix::net::UnixStream::connect(path)
+            .map_err(|err| WireguardError::Io(err.to_string()))?;
+
+        Ok(Self::new(stream))
+    }
+}
+
+impl<S: Read + Write> UapiClient<S> {
+    /// Creates client over already connected stream.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    /// Get device's state (UAPI `get=1` operation).
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::Io`] -- socket error.
+    /// - Errors of [`UapiDevice::parse()`].
+    pub fn get(&mut self) -> WireguardResult<UapiDevice> {
+        self.send("get=1\n\n")?;
+
+        UapiDevice::parse(&self.receive()?)
+    }
+
+    /// Replaces device's config with `interface` (UAPI `set=1` operation).
+    ///
+    /// Peers' endpoints are resolved with [`Peer::resolve_endpoint()`] before sending.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireguardError::Io`] -- socket error.
+    /// - [`WireguardError::UapiErrno`] -- device returned error.
+    /// - Errors of [`Peer::resolve_endpoint()`] and [`Interface::to_uapi()`].
+    pub fn set(&mut self, interface: &Interface) -> WireguardResult<()> {
+        let mut interface = interface.clone();
+        for peer in &mut interface.peers {
+            peer.endpoint = peer
+                .resolve_endpoint()?
+                .map(|endpoint| endpoint.to_string());
+        }
+
+        self.send(&interface.to_uapi()?)?;
+
+        UapiDevice::parse(&self.receive()?).map(|_| ())
+    }
+
+    fn send(&mut self, request: &str) -> WireguardResult<()> {
+        let stream = self.stream.get_mut();
+
+        stream
+            .write_all(request.as_bytes())
+            .and_then(|()| stream.flush())
+            .map_err(|err| WireguardError::Io(err.to_string()))
+    }
+
+    /// Reads response until empty line.
+    fn receive(&mut self) -> WireguardResult<String> {
+        let mut response = String::new();
+
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stream
+                .read_line(&mut line)
+                .map_err(|err| WireguardError::Io(err.to_string()))?;
+
+            if read == 0 || line == "\n" {
+                return Ok(response);
+            }
+
+            response.push_str(&line);
+        }
+    }
+}
@@ -13,6 +13,54 @@ fn amnezia_random() {
         .build();
 }
 
+#[test]
+fn amnezia_random_property() {
+    for _ in 0..10_000 {
+        let settings = AmneziaSettings::random();
+
+        assert_eq!(settings.validate(), Ok(()), "{settings:?}");
+        assert!((3..=10).contains(&settings.jc), "{settings:?}");
+        assert!((40..=60).contains(&settings.jmin), "{settings:?}");
+        assert!((900..=1000).contains(&settings.jmax), "{settings:?}");
+        assert!((15..=150).contains(&settings.s1), "{settings:?}");
+        assert!((15..=150).contains(&settings.s2), "{settings:?}");
+        assert_ne!(settings.s1 + 56, settings.s2, "{settings:?}");
+
+        let headers = [settings.h1, settings.h2, settings.h3, settings.h4];
+        for (index, header) in headers.iter().enumerate() {
+            assert_eq!(header.start, header.end, "{settings:?}");
+            assert!((5..=2_147_483_647).contains(&header.start), "{settings:?}");
+            assert!(!headers[index + 1..].contains(header), "{settings:?}");
+        }
+    }
+}
+
+#[test]
+fn amnezia_try_build() {
+    let amnezia_settings = AmneziaSettings {
+        jc: 999,
+        ..AmneziaSettings::random()
+    };
+
+    assert_eq!(
+        InterfaceBuilder::new()
+            .address("10.0.0.1/24".parse().unwrap())
+            .private_key(PrivateKey::random())
+            .amnezia_settings(amnezia_settings.clone())
+            .try_build()
+            .unwrap_err(),
+        WireguardError::InvalidAmneziaSetting("Jc".to_string())
+    );
+    assert_eq!(
+        PeerBuilder::new()
+            .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+            .amnezia_settings(amnezia_settings)
+            .try_build()
+            .unwrap_err(),
+        WireguardError::InvalidAmneziaSetting("Jc".to_string())
+    );
+}
+
 #[test]
 fn amnezia_validation() {
     let amnezia_settings = AmneziaSettings::random();
@@ -20,7 +68,7 @@ fn amnezia_validation() {
     {
         let settings = AmneziaSettings {
             jc: 999,
-            ..amnezia_settings
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -33,7 +81,7 @@ fn amnezia_validation() {
         let settings = AmneziaSettings {
             jmin: 800,
             jmax: 500,
-            ..amnezia_settings
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -45,7 +93,7 @@ fn amnezia_validation() {
     {
         let settings = AmneziaSettings {
             jmax: 9999,
-            ..amnezia_settings
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -57,7 +105,7 @@ fn amnezia_validation() {
     {
         let settings = AmneziaSettings {
             s1: 9999,
-            ..amnezia_settings
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -70,7 +118,7 @@ fn amnezia_validation() {
         let settings = AmneziaSettings {
             s1: 100 - 56,
             s2: 100,
-            ..amnezia_settings
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -82,7 +130,7 @@ fn amnezia_validation() {
     {
         let settings = AmneziaSettings {
             s2: 9999,
-            ..amnezia_settings
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -93,11 +141,54 @@ fn amnezia_validation() {
 
     {
         let settings = AmneziaSettings {
-            h1: 222,
-            h2: 222,
-            h3: 333,
-            h4: 444,
-            ..amnezia_settings
+            h1: 222.into(),
+            h2: 222.into(),
+            h3: 333.into(),
+            h4: 444.into(),
+            ..amnezia_settings.clone()
+        };
+
+        assert_eq!(
+            settings.validate(),
+            Err(WireguardError::InvalidAmneziaSetting(
+                "H1/H2/H3/H4".to_string()
+            ))
+        );
+    }
+
+    {
+        let settings = AmneziaSettings {
+            s1: 100,
+            s2: 150,
+            s3: Some(100 + 148 - 64),
+            ..amnezia_settings.clone()
+        };
+
+        assert_eq!(
+            settings.validate(),
+            Err(WireguardError::InvalidAmneziaSetting("S3".to_string()))
+        );
+    }
+
+    {
+        let settings = AmneziaSettings {
+            s4: Some(9999),
+            ..amnezia_settings.clone()
+        };
+
+        assert_eq!(
+            settings.validate(),
+            Err(WireguardError::InvalidAmneziaSetting("S4".to_string()))
+        );
+    }
+
+    {
+        let settings = AmneziaSettings {
+            h1: MagicHeader::new(100, 200),
+            h2: MagicHeader::new(200, 300),
+            h3: 400.into(),
+            h4: 500.into(),
+            ..amnezia_settings.clone()
         };
 
         assert_eq!(
@@ -107,4 +198,527 @@ fn amnezia_validation() {
             ))
         );
     }
+
+    {
+        let settings = AmneziaSettings {
+            h1: 100.into(),
+            h2: 200.into(),
+            h3: MagicHeader::new(400, 300),
+            h4: 500.into(),
+            ..amnezia_settings.clone()
+        };
+
+        assert_eq!(
+            settings.validate(),
+            Err(WireguardError::InvalidAmneziaSetting("H3".to_string()))
+        );
+    }
+
+    {
+        let settings = AmneziaSettings {
+            i2: Some("<r 2000>".parse().unwrap()),
+            ..amnezia_settings
+        };
+
+        assert_eq!(
+            settings.validate(),
+            Err(WireguardError::InvalidAmneziaSetting("I2".to_string()))
+        );
+    }
+}
+
+#[test]
+fn amnezia_v2_display() {
+    let settings = AmneziaSettings {
+        jc: 4,
+        jmin: 40,
+        jmax: 70,
+        s1: 50,
+        s2: 60,
+        s3: Some(30),
+        s4: Some(10),
+        h1: MagicHeader::new(100, 199),
+        h2: MagicHeader::new(200, 299),
+        h3: MagicHeader::new(300, 399),
+        h4: 400.into(),
+        i1: Some("<b 0xc70000000108><r 16><t><c>".parse().unwrap()),
+        i2: None,
+        i3: Some("<r 32>".parse().unwrap()),
+        i4: None,
+        i5: None,
+    };
+
+    assert_eq!(settings.validate(), Ok(()));
+    assert_eq!(
+        settings.to_string(),
+        "\
+Jc = 4
+Jmin = 40
+Jmax = 70
+S1 = 50
+S2 = 60
+S3 = 30
+S4 = 10
+H1 = 100-199
+H2 = 200-299
+H3 = 300-399
+H4 = 400
+I1 = <b 0xc70000000108><r 16><t><c>
+I3 = <r 32>
+"
+    );
+}
+
+#[test]
+fn amnezia_magic_header_parse() {
+    assert_eq!("1234".parse(), Ok(MagicHeader::from(1234)));
+    assert_eq!("10-20".parse(), Ok(MagicHeader::new(10, 20)));
+    assert_eq!(
+        "10-".parse::<MagicHeader>(),
+        Err(WireguardError::InvalidAmneziaSetting("10-".to_string()))
+    );
+    assert!(MagicHeader::new(10, 20).overlaps(&MagicHeader::new(20, 30)));
+    assert!(!MagicHeader::new(10, 20).overlaps(&21.into()));
+}
+
+#[test]
+fn amnezia_signature_packet_parse() {
+    let packet: SignaturePacket = " <b 0xDEADbeef> <c>\t<r 8><t> ".parse().unwrap();
+
+    assert_eq!(
+        packet.tags,
+        vec![
+            SignatureTag::Bytes(vec![0xde, 0xad, 0xbe, 0xef]),
+            SignatureTag::Counter,
+            SignatureTag::Random(8),
+            SignatureTag::Timestamp,
+        ]
+    );
+    assert_eq!(packet.len(), 20);
+    assert_eq!(packet.to_string(), "<b 0xdeadbeef><c><r 8><t>");
+
+    for (template, error) in [
+        ("<b 0xabc>", "<b 0xabc>"),
+        ("<b abcd>", "<b abcd>"),
+        ("<b 0xzz>", "<b 0xzz>"),
+        ("<r>", "<r>"),
+        ("<r -1>", "<r -1>"),
+        ("<t 1>", "<t 1>"),
+        ("<x>", "<x>"),
+        ("<r 4><t", "<t"),
+        ("garbage<t>", "garbage<t>"),
+    ] {
+        assert_eq!(
+            template.parse::<SignaturePacket>(),
+            Err(WireguardError::InvalidAmneziaSetting(error.to_string())),
+            "{template}"
+        );
+    }
+}
+
+#[test]
+fn amnezia_to_builder() {
+    let amnezia_settings = AmneziaSettings::random();
+
+    let interface = InterfaceBuilder::new()
+        .amnezia_settings(amnezia_settings.clone())
+        .build()
+        .to_builder()
+        .build();
+    let peer = PeerBuilder::new()
+        .amnezia_settings(amnezia_settings.clone())
+        .build()
+        .to_builder()
+        .build();
+
+    assert_eq!(interface.amnezia_settings.unwrap().h1, amnezia_settings.h1);
+    assert_eq!(peer.amnezia_settings.unwrap().h1, amnezia_settings.h1);
+}
+
+#[test]
+fn amnezia_client_shared_values() {
+    let server_settings = AmneziaSettings::random();
+    let peer_settings = AmneziaSettings {
+        jc: 7,
+        ..AmneziaSettings::random()
+    };
+
+    let peer = PeerBuilder::new()
+        .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+        .amnezia_settings(peer_settings)
+        .build();
+    let server = InterfaceBuilder::new()
+        .address("10.0.0.1/24".parse().unwrap())
+        .amnezia_settings(server_settings.clone())
+        .add_peer(peer.clone())
+        .build();
+
+    let client_settings = peer
+        .to_interface(&server)
+        .unwrap()
+        .amnezia_settings
+        .unwrap();
+    assert_eq!(client_settings.jc, 7);
+    assert!(client_settings.shared_values_match(&server_settings));
+    assert_eq!(client_settings.validate(), Ok(()));
+
+    let plain_peer = PeerBuilder::new()
+        .add_allowed_ip("10.0.0.3/32".parse().unwrap())
+        .build();
+    assert_eq!(
+        plain_peer.to_interface(&server).unwrap().amnezia_settings,
+        Some(server_settings)
+    );
+}
+
+#[test]
+fn amnezia_shared_values_mismatch() {
+    let server_settings = AmneziaSettings::random();
+
+    let matching = PeerBuilder::new()
+        .name("Matching".to_string())
+        .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+        .amnezia_settings(AmneziaSettings {
+            jc: 9,
+            jmin: 10,
+            jmax: 20,
+            ..server_settings.clone()
+        })
+        .build();
+    let mismatching = PeerBuilder::new()
+        .name("Mismatching".to_string())
+        .add_allowed_ip("10.0.0.3/32".parse().unwrap())
+        .amnezia_settings(AmneziaSettings {
+            s4: Some(16),
+            ..server_settings.clone()
+        })
+        .build();
+
+    let server = InterfaceBuilder::new()
+        .address("10.0.0.1/24".parse().unwrap())
+        .private_key(PrivateKey::random())
+        .amnezia_settings(server_settings)
+        .add_peer(matching)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        server
+            .to_builder()
+            .add_peer(mismatching.clone())
+            .try_build()
+            .unwrap_err(),
+        WireguardError::AmneziaMismatch("Mismatching".to_string())
+    );
+    assert_eq!(
+        InterfaceBuilder::new()
+            .address("10.0.0.1/24".parse().unwrap())
+            .private_key(PrivateKey::random())
+            .add_peer(mismatching.clone())
+            .try_build()
+            .unwrap_err(),
+        WireguardError::AmneziaMismatch("Mismatching".to_string())
+    );
+
+    let vanilla = PeerBuilder::new()
+        .name("Vanilla".to_string())
+        .add_allowed_ip("10.0.0.4/32".parse().unwrap())
+        .build();
+    let server_with_vanilla = server.to_builder().add_peer(vanilla).try_build().unwrap();
+    assert_eq!(
+        server_with_vanilla.amnezia_warnings(),
+        vec![AmneziaWarning::VanillaPeer("Vanilla".to_string())]
+    );
+
+    // obfuscated client can't connect to vanilla server
+    let vanilla_server = InterfaceBuilder::new()
+        .address("10.0.0.1/24".parse().unwrap())
+        .build();
+    assert_eq!(
+        mismatching.to_interface(&vanilla_server).unwrap_err(),
+        WireguardError::AmneziaMismatch("Mismatching".to_string())
+    );
+}
+
+#[test]
+fn amnezia_builder() {
+    let settings = AmneziaSettingsBuilder::new()
+        .jc(4)
+        .jmax(30)
+        .s2(100)
+        .s4(8)
+        .h2(MagicHeader::new(5, 2_000_000_000))
+        .i1("<r 8>".parse().unwrap())
+        .build()
+        .unwrap();
+
+    assert_eq!(settings.jc, 4);
+    assert!(settings.jmin < 30);
+    assert_eq!(settings.jmax, 30);
+    assert!((15..=150).contains(&settings.s1));
+    assert_eq!(settings.s2, 100);
+    assert_eq!(settings.s3, None);
+    assert_eq!(settings.s4, Some(8));
+    assert_eq!(settings.h2, MagicHeader::new(5, 2_000_000_000));
+    assert!(settings.h1.start > 2_000_000_000);
+    assert_eq!(settings.i1, Some("<r 8>".parse().unwrap()));
+    assert_eq!(settings.i2, None);
+
+    let settings = AmneziaSettingsBuilder::new().build().unwrap();
+    assert!((3..=10).contains(&settings.jc));
+    assert!((40..=60).contains(&settings.jmin));
+    assert!(settings.jmin < settings.jmax && settings.jmax <= 1000);
+}
+
+#[test]
+fn amnezia_builder_errors() {
+    assert_eq!(
+        AmneziaSettingsBuilder::new().jc(0).build(),
+        Err(WireguardError::InvalidAmneziaSetting("Jc".to_string()))
+    );
+    assert_eq!(
+        AmneziaSettingsBuilder::new().jmin(100).jmax(50).build(),
+        Err(WireguardError::InvalidAmneziaSetting("Jmin".to_string()))
+    );
+    assert_eq!(
+        AmneziaSettingsBuilder::new().s1(100).s2(156).build(),
+        Err(WireguardError::InvalidAmneziaSetting("S1".to_string()))
+    );
+    assert_eq!(
+        AmneziaSettingsBuilder::new()
+            .h1(10.into())
+            .h3(10.into())
+            .build(),
+        Err(WireguardError::InvalidAmneziaSetting(
+            "H1/H2/H3/H4".to_string()
+        ))
+    );
+}
+
+#[test]
+fn amnezia_builder_exhausted_headers() {
+    assert_eq!(
+        AmneziaSettingsBuilder::new()
+            .h1(MagicHeader::new(5, 2_147_483_647))
+            .build(),
+        Err(WireguardError::InvalidAmneziaSetting("H2".to_string()))
+    );
+
+    // single free header is found without long drawing
+    let settings = AmneziaSettingsBuilder::new()
+        .h1(MagicHeader::new(5, 1_000_000_000))
+        .h2(MagicHeader::new(1_000_000_002, 2_000_000_000))
+        .h3(MagicHeader::new(2_000_000_001, 2_147_483_647))
+        .build()
+        .unwrap();
+    assert_eq!(settings.h4, MagicHeader::from(1_000_000_001));
+
+    assert_eq!(
+        AmneziaSettingsBuilder::new().jmin(usize::MAX).build(),
+        Err(WireguardError::InvalidAmneziaSetting("Jmin".to_string()))
+    );
+}
+
+#[test]
+fn amnezia_builder_pinned_s3() {
+    for _ in 0..500 {
+        let settings = AmneziaSettingsBuilder::new().s3(100).build().unwrap();
+
+        assert_eq!(settings.s3, Some(100));
+        assert_ne!(settings.s1 + 148, 100 + 64);
+        assert_ne!(settings.s2 + 92, 100 + 64);
+    }
+}
+
+#[test]
+fn amnezia_parse_config() {
+    let settings = AmneziaSettingsBuilder::new()
+        .s3(30)
+        .s4(10)
+        .h1(MagicHeader::new(100, 199))
+        .i1("<b 0xc0ff><r 16><t><c>".parse().unwrap())
+        .build()
+        .unwrap();
+    let peer = PeerBuilder::new()
+        .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+        .build();
+    let interface = InterfaceBuilder::new()
+        .address("10.0.0.1/24".parse().unwrap())
+        .listen_port(51820)
+        .amnezia_settings(settings.clone())
+        .add_peer(peer)
+        .build();
+
+    let document = ConfigDocument::parse(&interface.to_string()).unwrap();
+    let parsed = Interface::try_from(&document).unwrap();
+
+    assert_eq!(parsed.amnezia_settings, Some(settings));
+    assert_eq!(parsed.to_string(), interface.to_string());
+}
+
+#[test]
+fn amnezia_parse_server_config_try_build() {
+    let config = "\
+[Interface]
+Address = 10.0.0.1/24
+ListenPort = 51820
+PrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+Jc = 4
+Jmin = 40
+Jmax = 70
+S1 = 50
+S2 = 60
+H1 = 100
+H2 = 200
+H3 = 300
+H4 = 400
+
+[Peer]
+PublicKey = xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=
+AllowedIPs = 10.0.0.2/32
+";
+
+    let interface = config.parse::<Interface>().unwrap();
+    let rebuilt = interface.to_builder().try_build().unwrap();
+
+    assert_eq!(rebuilt.to_string(), interface.to_string());
+}
+
+#[test]
+fn amnezia_parse_config_errors() {
+    const BASE: &str = "\
+[Interface]
+Address = 10.0.0.1/24
+PrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+";
+    const VALUES: &str = "\
+Jc = 4
+Jmin = 40
+Jmax = 70
+S1 = 50
+S2 = 60
+H1 = 1
+H2 = 2
+H3 = 3
+H4 = 4
+";
+
+    let parse = |config: &str| Interface::try_from(&ConfigDocument::parse(config).unwrap());
+
+    assert_eq!(parse(BASE).unwrap().amnezia_settings, None);
+    assert_eq!(
+        parse(&format!("{BASE}{VALUES}"))
+            .unwrap()
+            .amnezia_settings
+            .unwrap()
+            .h4,
+        4.into()
+    );
+
+    for (config, error) in [
+        (format!("{BASE}Jc = 4\n"), "Jmin"),
+        (format!("{BASE}{}", VALUES.replace("S2 = 60", "")), "S2"),
+        (
+            format!("{BASE}{}", VALUES.replace("Jc = 4", "Jc = x")),
+            "Jc",
+        ),
+        (
+            format!("{BASE}{}", VALUES.replace("H3 = 3", "H3 = 3-")),
+            "H3",
+        ),
+        (format!("{BASE}{VALUES}I2 = <q>\n"), "I2"),
+        (
+            format!("{BASE}{}", VALUES.replace("H3 = 3", "H3 = 2")),
+            "H1/H2/H3/H4",
+        ),
+    ] {
+        assert_eq!(
+            parse(&config).unwrap_err(),
+            WireguardError::InvalidAmneziaSetting(error.to_string()),
+            "{config}"
+        );
+    }
+}
+
+#[test]
+fn amnezia_to_vanilla() {
+    let settings = AmneziaSettings::random();
+    let peer = PeerBuilder::new()
+        .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+        .amnezia_settings(settings.clone())
+        .build();
+    let interface = InterfaceBuilder::new()
+        .address("10.0.0.1/24".parse().unwrap())
+        .listen_port(51820)
+        .amnezia_settings(settings)
+        .add_peer(peer.clone())
+        .build();
+
+    let vanilla = interface.to_vanilla();
+
+    assert_eq!(vanilla.amnezia_settings, None);
+    assert_eq!(vanilla.peers[0].amnezia_settings, None);
+    assert_eq!(vanilla.peers[0].public_key(), peer.public_key());
+    assert_eq!(
+        vanilla.to_string(),
+        format!(
+            "[Interface]
+Address = 10.0.0.1/24
+ListenPort = 51820
+PrivateKey = {}
+
+[Peer]
+AllowedIPs = 10.0.0.2/32
+PublicKey = {}
+
+",
+            interface.private_key,
+            peer.public_key()
+        )
+    );
+    assert!(vanilla.amnezia_warnings().is_empty());
+}
+
+#[test]
+fn amnezia_compatibility_warnings() {
+    let settings = AmneziaSettings::random();
+    let obfuscated = PeerBuilder::new()
+        .name("Obfuscated".to_string())
+        .add_allowed_ip("10.0.0.2/32".parse().unwrap())
+        .amnezia_settings(settings.clone())
+        .build();
+    let vanilla = PeerBuilder::new()
+        .add_allowed_ip("10.0.0.3/32".parse().unwrap())
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address("10.0.0.1/24".parse().unwrap())
+        .amnezia_settings(settings)
+        .add_peer(obfuscated.clone())
+        .add_peer(vanilla.clone())
+        .build();
+
+    let warnings = interface.amnezia_warnings();
+    assert_eq!(
+        warnings,
+        vec![AmneziaWarning::VanillaPeer(
+            vanilla.public_key().to_string()
+        )]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        format!(
+            "peer {} is vanilla WireGuard, but interface uses AmneziaWG: handshake would fail",
+            vanilla.public_key()
+        )
+    );
+
+    let interface = Interface {
+        amnezia_settings: None,
+        ..interface
+    };
+    assert_eq!(
+        interface.amnezia_warnings(),
+        vec![AmneziaWarning::ObfuscatedPeer("Obfuscated".to_string())]
+    );
 }
@@ -0,0 +1,211 @@
+#![cfg(feature = "amneziavpn")]
+
+use base64::prelude::*;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use ipnet::Ipv4Net;
+use serde_json::Value;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+use std::io::{Read, Write};
+
+fn get_example_data() -> (Interface, Interface) {
+    let client = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .build();
+
+    let server = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .endpoint("vpn.example.com:51820".to_string())
+        .set_dns(vec!["1.1.1.1".to_string(), "1.0.0.1".to_string()])
+        .amnezia_settings(AmneziaSettings::random())
+        .add_peer(client.clone())
+        .build();
+
+    let mut client = client.to_interface(&server).unwrap();
+    client.mtu = Some(1280);
+    client.peers[0].preshared_key = Some(PresharedKey::random());
+    client.peers[0].persistent_keepalive = Some(25);
+
+    (server, client)
+}
+
+#[test]
+fn amnezia_vpn_json() {
+    let (server, client) = get_example_data();
+    let settings = client.amnezia_settings.clone().unwrap();
+
+    let config: Value = serde_json::from_str(&client.to_amnezia_json("My VPN").unwrap()).unwrap();
+
+    assert_eq!(config["description"], "My VPN");
+    assert_eq!(config["hostName"], "vpn.example.com");
+    assert_eq!(config["dns1"], "1.1.1.1");
+    assert_eq!(config["dns2"], "1.0.0.1");
+    assert_eq!(config["defaultContainer"], "amnezia-awg");
+
+    let container = &config["containers"][0];
+    assert_eq!(container["container"], "amnezia-awg");
+    assert_eq!(container["awg"]["port"], "51820");
+    assert_eq!(container["awg"]["Jc"], settings.jc.to_string());
+    assert_eq!(container["awg"]["H1"], settings.h1.to_string());
+
+    let last_config: Value =
+        serde_json::from_str(container["awg"]["last_config"].as_str().unwrap()).unwrap();
+    assert_eq!(last_config["client_ip"], "10.0.0.2");
+    assert_eq!(
+        last_config["client_priv_key"],
+        client.private_key.to_string()
+    );
+    assert_eq!(
+        last_config["server_pub_key"],
+        server.to_peer().public_key().to_string()
+    );
+    assert_eq!(
+        last_config["psk_key"],
+        client.peers[0].preshared_key.as_ref().unwrap().to_string()
+    );
+    assert_eq!(
+        last_config["allowed_ips"],
+        serde_json::json!(["10.0.0.1/24"])
+    );
+    assert_eq!(last_config["mtu"], "1280");
+    assert_eq!(last_config["persistent_keep_alive"], "25");
+    assert_eq!(last_config["port"], 51820);
+    assert_eq!(last_config["S2"], settings.s2.to_string());
+    assert_eq!(last_config["config"], client.to_string());
+}
+
+#[test]
+fn amnezia_vpn_link() {
+    let (_, client) = get_example_data();
+
+    let link = client.to_amnezia_link("My VPN").unwrap();
+    let data = BASE64_URL_SAFE_NO_PAD
+        .decode(link.strip_prefix("vpn://").unwrap())
+        .unwrap();
+
+    let mut json = String::new();
+    ZlibDecoder::new(&data[4..])
+        .read_to_string(&mut json)
+        .unwrap();
+    assert_eq!(data[..4], u32::try_from(json.len()).unwrap().to_be_bytes());
+    assert_eq!(json, client.to_amnezia_json("My VPN").unwrap());
+
+    assert_eq!(
+        Interface::from_amnezia_link(&link).unwrap().to_string(),
+        client.to_string()
+    );
+}
+
+#[test]
+fn amnezia_vpn_import() {
+    let (_, client) = get_example_data();
+    let json = client.to_amnezia_json("My VPN").unwrap();
+
+    assert_eq!(
+        Interface::from_amnezia_json(&json).unwrap().to_string(),
+        client.to_string()
+    );
+
+    let uncompressed_link = format!("vpn://{}", BASE64_URL_SAFE.encode(&json));
+    assert_eq!(
+        Interface::from_amnezia_link(&uncompressed_link)
+            .unwrap()
+            .to_string(),
+        client.to_string()
+    );
+}
+
+#[test]
+fn amnezia_vpn_errors() {
+    let (server, client) = get_example_data();
+
+    assert_eq!(
+        Interface {
+            amnezia_settings: None,
+            ..client.clone()
+        }
+        .to_amnezia_json("My VPN")
+        .unwrap_err(),
+        WireguardError::MissingAmneziaSettings
+    );
+    assert_eq!(
+        Interface {
+            peers: Vec::new(),
+            ..client.clone()
+        }
+        .to_amnezia_json("My VPN")
+        .unwrap_err(),
+        WireguardError::MissingSection("Peer".to_string())
+    );
+    assert_eq!(
+        server.to_amnezia_json("My VPN").unwrap_err(),
+        WireguardError::InvalidValue("Endpoint".to_string())
+    );
+
+    assert_eq!(
+        Interface::from_amnezia_link("https://example.com").unwrap_err(),
+        WireguardError::InvalidValue("vpn://".to_string())
+    );
+    assert_eq!(
+        Interface::from_amnezia_link("vpn://!!!").unwrap_err(),
+        WireguardError::InvalidValue("vpn://".to_string())
+    );
+
+    // corrupt compressed data isn't treated as JSON
+    let mut data = BASE64_URL_SAFE_NO_PAD
+        .decode(
+            client
+                .to_amnezia_link("My VPN")
+                .unwrap()
+                .strip_prefix("vpn://")
+                .unwrap(),
+        )
+        .unwrap();
+    data.truncate(data.len() / 2);
+    assert_eq!(
+        Interface::from_amnezia_link(&format!("vpn://{}", BASE64_URL_SAFE_NO_PAD.encode(&data)))
+            .unwrap_err(),
+        WireguardError::InvalidValue("vpn://".to_string())
+    );
+
+    // data, that is bigger than size in header, isn't decompressed
+    let mut encoder = ZlibEncoder::new(16u32.to_be_bytes().to_vec(), Compression::best());
+    encoder.write_all(&vec![b' '; 1 << 20]).unwrap();
+    let bomb = encoder.finish().unwrap();
+    assert_eq!(
+        Interface::from_amnezia_link(&format!("vpn://{}", BASE64_URL_SAFE_NO_PAD.encode(&bomb)))
+            .unwrap_err(),
+        WireguardError::InvalidValue("vpn://".to_string())
+    );
+
+    // data with size in header above 1 MiB isn't decompressed, even if sizes match
+    let size = (1 << 20) + 1;
+    let mut encoder = ZlibEncoder::new(u32::to_be_bytes(size).to_vec(), Compression::best());
+    encoder.write_all(&vec![b' '; size as usize]).unwrap();
+    let large = encoder.finish().unwrap();
+    assert_eq!(
+        Interface::from_amnezia_link(&format!("vpn://{}", BASE64_URL_SAFE_NO_PAD.encode(&large)))
+            .unwrap_err(),
+        WireguardError::InvalidValue("vpn://".to_string())
+    );
+
+    // declared size can't make importer allocate gigabytes
+    let mut huge = u32::MAX.to_be_bytes().to_vec();
+    huge.extend_from_slice(&bomb[4..]);
+    assert_eq!(
+        Interface::from_amnezia_link(&format!("vpn://{}", BASE64_URL_SAFE_NO_PAD.encode(&huge)))
+            .unwrap_err(),
+        WireguardError::InvalidValue("vpn://".to_string())
+    );
+
+    assert_eq!(
+        Interface::from_amnezia_json("{}").unwrap_err(),
+        WireguardError::InvalidValue("containers".to_string())
+    );
+    assert_eq!(
+        Interface::from_amnezia_json(r#"{"containers": [{"container": "amnezia-xray"}]}"#)
+            .unwrap_err(),
+        WireguardError::InvalidValue("awg".to_string())
+    );
+}
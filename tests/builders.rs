@@ -38,3 +38,62 @@ fn peer_builder() {
     assert_eq!(peer.allowed_ips, vec![allowed_ip]);
     assert_eq!(peer.endpoint, Some(endpoint));
 }
+
+#[test]
+fn interface_try_build() {
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.3.2.1/24"))
+        .private_key(PrivateKey::random())
+        .listen_port(55870)
+        .add_dns("8.8.8.8".to_string())
+        .try_build()
+        .unwrap();
+
+    assert_eq!(interface.address, as_ipnet!("10.3.2.1/24"));
+
+    assert_eq!(
+        InterfaceBuilder::new().try_build().unwrap_err(),
+        WireguardError::NoAddressProvided
+    );
+    assert_eq!(
+        InterfaceBuilder::new()
+            .address(as_ipnet!("10.3.2.1/24"))
+            .try_build()
+            .unwrap_err(),
+        WireguardError::NoPrivateKeyProvided
+    );
+    assert_eq!(
+        InterfaceBuilder::new()
+            .address(as_ipnet!("10.3.2.1/24"))
+            .private_key(PrivateKey::random())
+            .listen_port(0)
+            .try_build()
+            .unwrap_err(),
+        WireguardError::InvalidListenPort
+    );
+    assert_eq!(
+        InterfaceBuilder::new()
+            .address(as_ipnet!("10.3.2.1/24"))
+            .private_key(PrivateKey::random())
+            .set_dns(vec!["8.8.8.8".to_string(), "1.1.1.1".to_string()])
+            .add_dns("8.8.8.8".to_string())
+            .try_build()
+            .unwrap_err(),
+        WireguardError::DuplicateDns("8.8.8.8".to_string())
+    );
+}
+
+#[test]
+fn peer_try_build() {
+    let peer = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.3.2.2/32"))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(peer.allowed_ips, vec![as_ipnet!("10.3.2.2/32")]);
+
+    assert_eq!(
+        PeerBuilder::new().try_build().unwrap_err(),
+        WireguardError::NoAllowedIPs
+    );
+}
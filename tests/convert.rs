@@ -29,3 +29,50 @@ fn interface_to_peer() {
 // #[test]
 // fn peer_to_interface() {
 // }
+
+#[test]
+fn interface_to_builder() {
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.3.2.1/24"))
+        .listen_port(55870)
+        .add_dns("8.8.8.8".to_string())
+        .endpoint("server.example.com".to_string())
+        .add_peer(
+            PeerBuilder::new()
+                .add_allowed_ip(as_ipnet!("10.3.2.2/32"))
+                .build(),
+        )
+        .build();
+
+    let modified = interface
+        .to_builder()
+        .add_dns("1.1.1.1".to_string())
+        .build();
+
+    assert_eq!(modified.address, interface.address);
+    assert_eq!(modified.listen_port, interface.listen_port);
+    assert_eq!(modified.private_key, interface.private_key);
+    assert_eq!(modified.dns, vec!["8.8.8.8", "1.1.1.1"]);
+    assert_eq!(modified.endpoint, interface.endpoint);
+    assert_eq!(modified.peers.len(), 1);
+}
+
+#[test]
+fn peer_to_builder() {
+    let preshared_key = PresharedKey::random();
+    let peer = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.3.2.2/32"))
+        .preshared_key(preshared_key.clone())
+        .persistent_keepalive(25)
+        .build();
+
+    let modified = PeerBuilder::from(peer.clone())
+        .endpoint("client.example.com".to_string())
+        .build();
+
+    assert_eq!(modified.allowed_ips, peer.allowed_ips);
+    assert_eq!(modified.key, peer.key);
+    assert_eq!(modified.preshared_key, Some(preshared_key));
+    assert_eq!(modified.persistent_keepalive, Some(25));
+    assert_eq!(modified.endpoint, Some("client.example.com".to_string()));
+}
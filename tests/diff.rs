@@ -0,0 +1,141 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+fn get_example_data() -> (Interface, Peer, Peer) {
+    let laptop = PeerBuilder::new()
+        .name("Laptop".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(PresharedKey::random())
+        .build();
+    let phone = PeerBuilder::new()
+        .name("Phone".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .set_dns(vec!["8.8.8.8".to_string()])
+        .add_peer(laptop.clone())
+        .add_peer(phone.clone())
+        .build();
+
+    (interface, laptop, phone)
+}
+
+#[test]
+fn diff_same() {
+    let (interface, _, _) = get_example_data();
+
+    let diff = interface.diff(&interface.clone());
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
+
+#[test]
+fn diff_interface_fields() {
+    let (old, _, _) = get_example_data();
+    let new = old
+        .to_builder()
+        .listen_port(51821)
+        .private_key(PrivateKey::random())
+        .set_dns(vec!["1.1.1.1".to_string()])
+        .build();
+
+    let diff = old.diff(&new);
+
+    assert_eq!(
+        diff.changes,
+        vec![
+            InterfaceChange::ListenPort {
+                old: Some(51820),
+                new: Some(51821)
+            },
+            InterfaceChange::PrivateKey,
+            InterfaceChange::Dns {
+                added: vec!["1.1.1.1".to_string()],
+                removed: vec!["8.8.8.8".to_string()]
+            },
+        ]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "~ ListenPort: 51820 -> 51821\n~ PrivateKey: rotated\n~ DNS: +1.1.1.1 -8.8.8.8\n"
+    );
+}
+
+#[test]
+fn diff_peers() {
+    let (old, laptop, phone) = get_example_data();
+
+    let tablet = PeerBuilder::new()
+        .name("Tablet".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+        .build();
+    let new_laptop = laptop
+        .to_builder()
+        .set_allowed_ips(vec![as_ipnet!("10.0.0.5/32")])
+        .endpoint("laptop.example.com:51820".to_string())
+        .preshared_key(PresharedKey::random())
+        .add_comment("moved to office".to_string())
+        .build();
+    let new = old
+        .to_builder()
+        .set_peers(vec![new_laptop, tablet.clone()])
+        .build();
+
+    let diff = old.diff(&new);
+
+    assert!(diff.changes.is_empty());
+    assert_eq!(diff.added_peers, vec![tablet.clone()]);
+    assert_eq!(diff.removed_peers, vec![phone.clone()]);
+    assert_eq!(diff.modified_peers.len(), 1);
+
+    let laptop_diff = &diff.modified_peers[0];
+    assert_eq!(laptop_diff.public_key, laptop.public_key());
+    assert_eq!(
+        laptop_diff.changes[0],
+        PeerChange::Comments {
+            added: vec!["moved to office".to_string()],
+            removed: vec![]
+        }
+    );
+    assert_eq!(
+        laptop_diff.changes[1],
+        PeerChange::AllowedIPs {
+            added: vec![as_ipnet!("10.0.0.5/32")],
+            removed: vec![as_ipnet!("10.0.0.2/32")]
+        }
+    );
+    assert_eq!(
+        laptop_diff.changes[2],
+        PeerChange::Endpoint {
+            old: None,
+            new: Some("laptop.example.com:51820".to_string())
+        }
+    );
+    assert!(matches!(
+        laptop_diff.changes[3],
+        PeerChange::PresharedKey {
+            old: Some(_),
+            new: Some(_)
+        }
+    ));
+
+    assert_eq!(
+        diff.to_string(),
+        format!(
+            "+ [Peer] {} (Tablet)\n\
+             - [Peer] {} (Phone)\n\
+             ~ [Peer] {} (Laptop)\n    \
+             Comments: +\"moved to office\"\n    \
+             AllowedIPs: +10.0.0.5/32 -10.0.0.2/32\n    \
+             Endpoint: (none) -> laptop.example.com:51820\n    \
+             PresharedKey: rotated\n",
+            tablet.public_key(),
+            phone.public_key(),
+            laptop.public_key(),
+        )
+    );
+}
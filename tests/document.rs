@@ -0,0 +1,274 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+const CONFIG: &str = "\
+# wg0 on gateway, edit with care
+[Interface]
+Address = 10.0.0.1/24, fd00::1/64
+ListenPort = 51820 # default port
+PrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+PostUp = iptables -A FORWARD -i %i -j ACCEPT
+SaveConfig = false
+
+[Peer]
+# Name = Laptop
+# Description = Office laptop
+# rotated keys on 2025-01-01
+PublicKey = xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=
+AllowedIPs = 10.0.0.2/32, fd00::2/128
+PersistentKeepalive = 25
+
+[Peer]
+PublicKey = TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=
+AllowedIPs = 10.0.0.3/32
+AllowedIPs = 10.0.1.0/24
+Endpoint = 192.0.2.1:51820";
+
+#[test]
+fn document_roundtrip() {
+    let document = ConfigDocument::parse(CONFIG).unwrap();
+    assert_eq!(document.to_string(), CONFIG);
+
+    let crlf = CONFIG.replace('\n', "\r\n");
+    let document = ConfigDocument::parse(&crlf).unwrap();
+    assert_eq!(document.to_string(), crlf);
+}
+
+#[test]
+fn document_section_header_comment() {
+    let config = CONFIG
+        .replace("[Interface]\n", "[Interface] # server\n")
+        .replace("[Peer]\n# Name", "[Peer]  #laptop\n# Name");
+    let document = ConfigDocument::parse(&config).unwrap();
+
+    assert_eq!(document.to_string(), config);
+    assert_eq!(
+        document
+            .sections()
+            .iter()
+            .map(Section::name)
+            .collect::<Vec<_>>(),
+        vec!["Interface", "Peer", "Peer"]
+    );
+    assert_eq!(
+        Interface::try_from(&document).unwrap().to_string(),
+        Interface::try_from(&ConfigDocument::parse(CONFIG).unwrap())
+            .unwrap()
+            .to_string()
+    );
+}
+
+#[test]
+fn document_edit() {
+    let mut document = ConfigDocument::parse(CONFIG).unwrap();
+
+    let interface = document.section_mut("Interface").unwrap();
+    interface.set("ListenPort", "51821".to_string());
+    interface.add("MTU".to_string(), "1420".to_string());
+    assert!(interface.remove("SaveConfig"));
+
+    let peer = document.sections_mut().last_mut().unwrap();
+    peer.set("AllowedIPs", "10.0.0.3/32".to_string());
+    peer.add("PersistentKeepalive".to_string(), "15".to_string());
+
+    let expected = CONFIG
+        .replace("ListenPort = 51820 # default port", "ListenPort = 51821")
+        .replace("SaveConfig = false", "MTU = 1420")
+        .replace(
+            "AllowedIPs = 10.0.0.3/32\nAllowedIPs = 10.0.1.0/24\nEndpoint = 192.0.2.1:51820",
+            "AllowedIPs = 10.0.0.3/32\nEndpoint = 192.0.2.1:51820\nPersistentKeepalive = 15\n",
+        );
+
+    assert_eq!(document.to_string(), expected);
+}
+
+#[test]
+fn document_add_section() {
+    let mut document = ConfigDocument::parse(CONFIG).unwrap();
+
+    let peer = PeerBuilder::new()
+        .name("Phone".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+        .build();
+    document.add_section(Section::try_from(&peer).unwrap());
+
+    let config = document.to_string();
+    assert!(config.starts_with(CONFIG));
+    assert!(config.ends_with(&format!("\n\n{peer}")));
+}
+
+#[test]
+fn document_invalid() {
+    assert_eq!(
+        ConfigDocument::parse("[Interface]\nAddress\n"),
+        Err(WireguardError::InvalidLine(2))
+    );
+    assert_eq!(
+        ConfigDocument::parse("Address = 10.0.0.1/24\n"),
+        Err(WireguardError::InvalidLine(1))
+    );
+    assert_eq!(
+        ConfigDocument::parse("[Interface\n"),
+        Err(WireguardError::InvalidLine(1))
+    );
+}
+
+#[test]
+fn document_to_interface() {
+    let interface: Interface = CONFIG.parse().unwrap();
+
+    assert_eq!(interface.address, as_ipnet!("10.0.0.1/24"));
+    assert_eq!(interface.listen_port, Some(51820));
+    assert_eq!(
+        interface.private_key.to_string(),
+        "yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk="
+    );
+    assert_eq!(interface.peers.len(), 2);
+
+    let laptop = &interface.peers[0];
+    assert_eq!(laptop.name, Some("Laptop".to_string()));
+    assert_eq!(laptop.description, Some("Office laptop".to_string()));
+    assert_eq!(laptop.comments, vec!["rotated keys on 2025-01-01"]);
+    assert_eq!(laptop.allowed_ips, vec![as_ipnet!("10.0.0.2/32")]);
+    assert_eq!(laptop.persistent_keepalive, Some(25));
+
+    let other = &interface.peers[1];
+    assert_eq!(other.name, None);
+    assert_eq!(
+        other.allowed_ips,
+        vec![as_ipnet!("10.0.0.3/32"), as_ipnet!("10.0.1.0/24")]
+    );
+    assert_eq!(other.endpoint, Some("192.0.2.1:51820".to_string()));
+}
+
+#[test]
+fn document_peer_metadata_comments() {
+    let peers = [
+        PeerBuilder::new()
+            .add_comment("Name = not a name".to_string())
+            .build(),
+        PeerBuilder::new()
+            .name("Laptop".to_string())
+            .add_comment("Description = not a description".to_string())
+            .add_comment("Name = still not a name".to_string())
+            .build(),
+        PeerBuilder::new()
+            .name("Laptop".to_string())
+            .description("Office laptop".to_string())
+            .add_comment("Name = not a name".to_string())
+            .build(),
+    ];
+
+    for peer in peers {
+        let peer = PeerBuilder::from(peer)
+            .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+            .build();
+        let interface = InterfaceBuilder::new()
+            .address(as_ipnet!("10.0.0.1/24"))
+            .add_peer(peer.clone())
+            .build();
+
+        let parsed: Interface = interface.to_string().parse().unwrap();
+        let parsed = &parsed.peers[0];
+        assert_eq!(parsed.name, peer.name);
+        assert_eq!(parsed.description, peer.description);
+        assert_eq!(parsed.comments, peer.comments);
+    }
+
+    // metadata comments are recognized only right after `[Peer]`
+    let config = CONFIG.replace(
+        "PersistentKeepalive = 25",
+        "PersistentKeepalive = 25\n# Name = Desktop",
+    );
+    let interface: Interface = config.parse().unwrap();
+    assert_eq!(interface.peers[0].name, Some("Laptop".to_string()));
+    assert_eq!(
+        interface.peers[0].comments,
+        vec!["rotated keys on 2025-01-01", "Name = Desktop"]
+    );
+}
+
+#[test]
+fn interface_roundtrip() {
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .fwmark(0xca6c)
+        .mtu(1420)
+        .set_dns(vec!["8.8.8.8".to_string(), "8.8.4.4".to_string()])
+        .endpoint("vpn.example.com".to_string())
+        .add_peer(
+            PeerBuilder::new()
+                .name("Laptop".to_string())
+                .add_comment("first peer".to_string())
+                .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+                .preshared_key(PresharedKey::random())
+                .build(),
+        )
+        .build();
+
+    let document = ConfigDocument::try_from(&interface).unwrap();
+    assert_eq!(document.to_string(), interface.to_string());
+
+    let parsed = Interface::try_from(&document).unwrap();
+    assert_eq!(parsed.to_string(), interface.to_string());
+}
+
+#[test]
+fn interface_to_document_errors() {
+    let peer = PeerBuilder::new()
+        .endpoint("vpn.example.com:51820\nbogus line".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .build();
+    assert!(matches!(
+        Section::try_from(&peer),
+        Err(WireguardError::InvalidLine(_))
+    ));
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .add_peer(peer)
+        .build();
+    assert!(matches!(
+        ConfigDocument::try_from(&interface),
+        Err(WireguardError::InvalidLine(_))
+    ));
+}
+
+#[test]
+fn interface_parse_errors() {
+    assert_eq!(
+        "[Peer]\n".parse::<Interface>().unwrap_err(),
+        WireguardError::MissingSection("Interface".to_string())
+    );
+    assert_eq!(
+        "[Interface]\nPrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\n"
+            .parse::<Interface>()
+            .unwrap_err(),
+        WireguardError::NoAddressProvided
+    );
+    assert_eq!(
+        "[Interface]\nAddress = 10.0.0.1/24\n"
+            .parse::<Interface>()
+            .unwrap_err(),
+        WireguardError::NoPrivateKeyProvided
+    );
+    assert_eq!(
+        "[Interface]\nAddress = 10.0.0.1/24\nPrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\nListenPort = port\n"
+            .parse::<Interface>()
+            .unwrap_err(),
+        WireguardError::InvalidValue("ListenPort".to_string())
+    );
+    assert_eq!(
+        "[Interface]\nAddress = 10.0.0.1/24\nPrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\nFwMark = mark\n"
+            .parse::<Interface>()
+            .unwrap_err(),
+        WireguardError::InvalidValue("FwMark".to_string())
+    );
+    assert_eq!(
+        "[Interface]\nAddress = 10.0.0.1/24\nPrivateKey = yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\n[Peer]\nAllowedIPs = 10.0.0.2/32\n"
+            .parse::<Interface>()
+            .unwrap_err(),
+        WireguardError::NoPublicKeyProvided
+    );
+}
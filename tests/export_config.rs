@@ -47,3 +47,59 @@ pub fn export_client_interface() {
     assert!(lines[3].starts_with("DNS = "));
     // TODO: asserts
 }
+
+#[test]
+pub fn export_peer_metadata() {
+    let peer = PeerBuilder::new()
+        .name("Laptop".to_string())
+        .description("Alice's work laptop".to_string())
+        .add_comment("issued 2025-01-01".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .build();
+
+    let config = peer.to_string();
+    let lines: Vec<&str> = config.split("\n").collect();
+
+    assert_eq!(lines[0], "[Peer]");
+    assert_eq!(lines[1], "# Name = Laptop");
+    assert_eq!(lines[2], "# Description = Alice's work laptop");
+    assert_eq!(lines[3], "# issued 2025-01-01");
+    assert_eq!(lines[4], "AllowedIPs = 10.0.0.2/32");
+}
+
+#[test]
+pub fn export_peer_metadata_line_breaks() {
+    let peer = PeerBuilder::new()
+        .name("Laptop\nEndpoint = evil.example.com:51820".to_string())
+        .description("first\r\nsecond".to_string())
+        .add_comment("line one\nPersistentKeepalive = 1".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .build();
+
+    let config = peer.to_string();
+    let lines: Vec<&str> = config.split("\n").collect();
+
+    assert_eq!(
+        lines[1],
+        "# Name = Laptop Endpoint = evil.example.com:51820"
+    );
+    assert_eq!(lines[2], "# Description = first  second");
+    assert_eq!(lines[3], "# line one");
+    assert_eq!(lines[4], "# PersistentKeepalive = 1");
+    assert_eq!(lines[5], "AllowedIPs = 10.0.0.2/32");
+    assert!(!config.contains("\nEndpoint"));
+}
+
+#[test]
+pub fn export_interface_endpoint_line_breaks() {
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .endpoint("vpn.example.com\nPostUp = rm -rf /".to_string())
+        .build();
+
+    let config = interface.to_string();
+    let lines: Vec<&str> = config.split("\n").collect();
+
+    assert_eq!(lines[1], "# Name = vpn.example.com PostUp = rm -rf /");
+    assert!(!config.contains("\nPostUp"));
+}
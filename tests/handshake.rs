@@ -0,0 +1,324 @@
+#![cfg(feature = "handshake")]
+
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+use std::{net::UdpSocket, thread, time::Duration};
+
+fn get_example_data() -> (Interface, Interface) {
+    let preshared_key = PresharedKey::random();
+    let client = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(preshared_key.clone())
+        .build();
+
+    let server = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .endpoint("vpn.example.com:51820".to_string())
+        .add_peer(
+            PeerBuilder::new()
+                .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+                .build(),
+        )
+        .add_peer(client.clone())
+        .build();
+
+    let mut client = client.to_interface(&server).unwrap();
+    client.peers[0].preshared_key = Some(preshared_key);
+
+    (server, client)
+}
+
+fn failed(reason: &str) -> WireguardResult<()> {
+    Err(WireguardError::HandshakeFailed(reason.to_string()))
+}
+
+#[test]
+fn handshake_success() {
+    let (server, client) = get_example_data();
+
+    assert_eq!(server.test_handshake(&client), Ok(()));
+
+    let server = Interface {
+        peers: server
+            .peers
+            .iter()
+            .map(|peer| Peer {
+                preshared_key: None,
+                ..peer.clone()
+            })
+            .collect(),
+        ..server
+    };
+    let mut client = client;
+    client.peers[0].preshared_key = None;
+    assert_eq!(server.test_handshake(&client), Ok(()));
+}
+
+#[test]
+fn handshake_wrong_server_key() {
+    let (server, mut client) = get_example_data();
+    client.peers[0].key = either::Either::Right(PublicKey::from(&PrivateKey::random()));
+
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("initiation's MAC1 is invalid: client's peer public key doesn't match server")
+    );
+}
+
+#[test]
+fn handshake_unknown_client() {
+    let (server, mut client) = get_example_data();
+    client.private_key = PrivateKey::random();
+
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("server doesn't have client's public key as peer")
+    );
+}
+
+#[test]
+fn handshake_preshared_key_mismatch() {
+    let (server, mut client) = get_example_data();
+
+    client.peers[0].preshared_key = Some(PresharedKey::random());
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("response can't be decrypted: preshared keys differ")
+    );
+
+    client.peers[0].preshared_key = None;
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("response can't be decrypted: preshared keys differ")
+    );
+}
+
+#[test]
+fn handshake_no_peers() {
+    let (server, mut client) = get_example_data();
+    client.peers.clear();
+
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("client doesn't have peers")
+    );
+}
+
+/// Spawns UDP responder of `server` on localhost, that answers first valid initiation and
+/// returns transport keys with number of skipped packets.
+fn spawn_responder(server: Interface) -> (u16, thread::JoinHandle<(SessionKeys, usize)>) {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let port = socket.local_addr().unwrap().port();
+
+    let handle = thread::spawn(move || {
+        let responder = HandshakeResponder::new(&server);
+        let mut buffer = [0; 2048];
+        let mut skipped = 0;
+
+        loop {
+            let (size, address) = socket.recv_from(&mut buffer).unwrap();
+            match responder.respond(&buffer[..size]) {
+                Ok((_, response, keys)) => {
+                    socket.send_to(&response, address).unwrap();
+                    return (keys, skipped);
+                }
+                Err(_) => skipped += 1,
+            }
+        }
+    });
+
+    (port, handle)
+}
+
+/// Probes responder on localhost `port`: sends `packets`, then initiation, and consumes
+/// response.
+fn probe(
+    initiator: &mut HandshakeInitiator,
+    packets: &[Vec<u8>],
+    port: u16,
+) -> WireguardResult<SessionKeys> {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    socket.connect(("127.0.0.1", port)).unwrap();
+
+    for packet in packets {
+        socket.send(packet).unwrap();
+    }
+    socket.send(&initiator.initiation()).unwrap();
+
+    let mut buffer = [0; 2048];
+    let size = socket.recv(&mut buffer).unwrap();
+
+    initiator.consume_response(&buffer[..size])
+}
+
+#[test]
+fn handshake_packets() {
+    let (server, client) = get_example_data();
+    let mut initiator = HandshakeInitiator::new(&client, &client.peers[0]);
+
+    let initiation = initiator.initiation();
+    assert_eq!(initiation.len(), 148);
+    assert_eq!(initiation[..4], [1, 0, 0, 0]);
+    assert_eq!(initiation[4..8], initiator.sender_index().to_le_bytes());
+    assert_eq!(initiation[132..], [0; 16]);
+
+    let responder = HandshakeResponder::new(&server);
+    let (peer, response, server_keys) = responder.respond(&initiation).unwrap();
+    assert_eq!(peer, &server.peers[1]);
+    assert_eq!(response.len(), 92);
+    assert_eq!(response[..4], [2, 0, 0, 0]);
+    assert_eq!(response[8..12], initiator.sender_index().to_le_bytes());
+
+    let client_keys = initiator.consume_response(&response).unwrap();
+    assert_eq!(client_keys.send, server_keys.receive);
+    assert_eq!(client_keys.receive, server_keys.send);
+
+    let mut truncated = initiation.clone();
+    truncated.pop();
+    assert_eq!(
+        responder.respond(&truncated).unwrap_err(),
+        WireguardError::HandshakeFailed("malformed handshake initiation".to_string())
+    );
+    assert_eq!(
+        initiator.consume_response(&initiation).unwrap_err(),
+        WireguardError::HandshakeFailed("malformed handshake response".to_string())
+    );
+}
+
+#[test]
+fn handshake_initiation_fresh_ephemeral() {
+    let (server, client) = get_example_data();
+    let mut initiator = HandshakeInitiator::new(&client, &client.peers[0]);
+
+    let first = initiator.initiation();
+    let first_index = initiator.sender_index();
+    let second = initiator.initiation();
+
+    // sender index, ephemeral key and encrypted static key must not repeat
+    assert_ne!(first[4..8], second[4..8]);
+    assert_ne!(first[8..40], second[8..40]);
+    assert_ne!(first[40..88], second[40..88]);
+    assert_ne!(first_index, initiator.sender_index());
+
+    // only the latest initiation's response is accepted
+    let responder = HandshakeResponder::new(&server);
+    let (_, response, _) = responder.respond(&first).unwrap();
+    assert!(initiator.consume_response(&response).is_err());
+
+    let (_, response, server_keys) = responder.respond(&second).unwrap();
+    let client_keys = initiator.consume_response(&response).unwrap();
+    assert_eq!(client_keys.send, server_keys.receive);
+}
+
+#[test]
+fn handshake_probe() {
+    let (server, client) = get_example_data();
+
+    let (port, responder) = spawn_responder(server);
+    let mut initiator = HandshakeInitiator::new(&client, &client.peers[0]);
+    let client_keys = probe(&mut initiator, &[], port).unwrap();
+
+    let (server_keys, skipped) = responder.join().unwrap();
+    assert_eq!(client_keys.send, server_keys.receive);
+    assert_eq!(client_keys.receive, server_keys.send);
+    assert_eq!(skipped, 0);
+}
+
+#[cfg(feature = "amneziawg")]
+#[test]
+fn handshake_probe_amnezia() {
+    let (mut server, mut client) = get_example_data();
+    let settings = AmneziaSettingsBuilder::new()
+        .jc(4)
+        .jmin(40)
+        .jmax(70)
+        .s1(20)
+        .s2(30)
+        .h1(MagicHeader::new(100, 200))
+        .h2(MagicHeader::new(300, 400))
+        .h3(MagicHeader::from(500))
+        .h4(MagicHeader::from(600))
+        .i1("<b 0xc0ff><r 8><c>".parse().unwrap())
+        .build()
+        .unwrap();
+    server.amnezia_settings = Some(settings.clone());
+    client.amnezia_settings = Some(settings);
+
+    let mut initiator = HandshakeInitiator::new(&client, &client.peers[0]);
+    let junk_packets = initiator.junk_packets();
+    assert_eq!(junk_packets.len(), 5);
+    assert_eq!(junk_packets[0].len(), 14);
+    assert_eq!(junk_packets[0][..2], [0xc0, 0xff]);
+    assert_eq!(junk_packets[0][10..], [0, 0, 0, 1]);
+    assert!(junk_packets[1..]
+        .iter()
+        .all(|packet| (40..=70).contains(&packet.len())));
+
+    let initiation = initiator.initiation();
+    assert_eq!(initiation.len(), 20 + 148);
+    let header = u32::from_le_bytes(initiation[20..24].try_into().unwrap());
+    assert!((100..=200).contains(&header));
+
+    let (_, response, _) = HandshakeResponder::new(&server)
+        .respond(&initiation)
+        .unwrap();
+    assert_eq!(response.len(), 30 + 92);
+    let header = u32::from_le_bytes(response[30..34].try_into().unwrap());
+    assert!((300..=400).contains(&header));
+
+    // vanilla responder can't parse obfuscated initiation
+    let vanilla = Interface {
+        amnezia_settings: None,
+        ..server.clone()
+    };
+    assert_eq!(
+        HandshakeResponder::new(&vanilla)
+            .respond(&initiation)
+            .unwrap_err(),
+        WireguardError::HandshakeFailed("malformed handshake initiation".to_string())
+    );
+
+    let (port, responder) = spawn_responder(server);
+    let client_keys = probe(&mut initiator, &junk_packets, port).unwrap();
+
+    let (server_keys, skipped) = responder.join().unwrap();
+    assert_eq!(client_keys.send, server_keys.receive);
+    assert_eq!(client_keys.receive, server_keys.send);
+    assert_eq!(skipped, junk_packets.len());
+}
+
+#[cfg(feature = "amneziawg")]
+#[test]
+fn handshake_amnezia() {
+    let (mut server, mut client) = get_example_data();
+    let settings = AmneziaSettings::random();
+
+    server.amnezia_settings = Some(settings.clone());
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("AmneziaWG values differ")
+    );
+
+    client.amnezia_settings = Some(AmneziaSettings {
+        jc: 10,
+        ..settings.clone()
+    });
+    assert_eq!(server.test_handshake(&client), Ok(()));
+
+    client.amnezia_settings = Some(AmneziaSettings {
+        s1: settings.s1 + 1,
+        ..settings
+    });
+    assert_eq!(
+        server.test_handshake(&client),
+        failed("AmneziaWG values differ")
+    );
+}
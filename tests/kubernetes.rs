@@ -0,0 +1,201 @@
+#![cfg(feature = "kubernetes")]
+
+use base64::prelude::*;
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+fn get_example_data() -> Interface {
+    let laptop = PeerBuilder::new()
+        .name("Alice's Laptop".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(PresharedKey::random())
+        .build();
+    let phone = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .build();
+
+    InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .endpoint("vpn.example.com:51820".to_string())
+        .add_peer(laptop)
+        .add_peer(phone)
+        .build()
+}
+
+/// Get `data` entries of manifest: `(key, value)`.
+fn data_entries(manifest: &str) -> Vec<(&str, &str)> {
+    manifest
+        .split_once("\ndata:\n")
+        .unwrap()
+        .1
+        .lines()
+        .filter_map(|line| line.strip_prefix("  ")?.split_once(": "))
+        .collect()
+}
+
+#[test]
+fn kubernetes_secret() {
+    let server = get_example_data();
+
+    let secret = KubernetesManifest::new("WireGuard Server")
+        .namespace("vpn")
+        .label(
+            "app.kubernetes.io/name".to_string(),
+            "wireguard".to_string(),
+        )
+        .add_interface("wg0", &server)
+        .secret();
+
+    assert!(secret.starts_with(
+        "\
+apiVersion: v1
+kind: Secret
+metadata:
+  name: \"wireguard-server\"
+  namespace: \"vpn\"
+  labels:
+    \"app.kubernetes.io/name\": \"wireguard\"
+type: Opaque
+data:
+"
+    ));
+
+    let entries = data_entries(&secret);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, "wg0.conf");
+    assert_eq!(
+        BASE64_STANDARD.decode(entries[0].1).unwrap(),
+        server.to_string().into_bytes()
+    );
+}
+
+#[test]
+fn kubernetes_clients() {
+    let server = get_example_data();
+    let phone_key = server.peers[1]
+        .public_key()
+        .to_string()
+        .replace('+', "-")
+        .replace('/', "_")
+        .replace('=', "");
+
+    let manifest = KubernetesManifest::new("clients")
+        .add_clients(&server)
+        .unwrap();
+    let secret = manifest.secret();
+    let entries = data_entries(&secret);
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].0, "Alice-s-Laptop.conf");
+    assert_eq!(entries[1].0, format!("{phone_key}.conf"));
+    assert_eq!(
+        BASE64_STANDARD.decode(entries[0].1).unwrap(),
+        server.peers[0]
+            .to_interface(&server)
+            .unwrap()
+            .to_string()
+            .into_bytes()
+    );
+
+    // same input gives same manifest
+    assert_eq!(
+        KubernetesManifest::new("clients")
+            .add_clients(&server)
+            .unwrap()
+            .secret(),
+        secret
+    );
+
+    let server = server
+        .to_builder()
+        .add_peer(
+            PeerBuilder::new()
+                .public_key(PublicKey::from(&PrivateKey::random()))
+                .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+                .build(),
+        )
+        .build();
+    assert_eq!(
+        KubernetesManifest::new("clients")
+            .add_clients(&server)
+            .unwrap_err(),
+        WireguardError::NoPrivateKeyProvided
+    );
+}
+
+#[test]
+fn kubernetes_config_map() {
+    let server = get_example_data();
+
+    let config_map = KubernetesManifest::new("wireguard")
+        .add_interface("wg0", &server)
+        .config_map()
+        .unwrap();
+
+    assert!(config_map.starts_with(
+        "\
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: \"wireguard\"
+data:
+  wg0.conf: |+
+    [Interface]
+"
+    ));
+    assert!(!config_map.contains("PrivateKey"));
+    assert!(!config_map.contains("PresharedKey"));
+    assert!(!config_map.contains(&server.private_key.to_string()));
+    assert!(config_map.contains(&format!(
+        "    PublicKey = {}\n",
+        server.peers[0].public_key()
+    )));
+
+    let broken = server
+        .to_builder()
+        .add_dns("1.1.1.1\nbogus line".to_string())
+        .build();
+    assert!(matches!(
+        KubernetesManifest::new("wireguard")
+            .add_interface("wg0", &broken)
+            .config_map(),
+        Err(WireguardError::InvalidLine(_))
+    ));
+}
+
+#[test]
+fn kubernetes_clients_same_name() {
+    let server = get_example_data();
+    let server = server
+        .to_builder()
+        .add_peer(
+            PeerBuilder::new()
+                .name("Alice's Laptop".to_string())
+                .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+                .build(),
+        )
+        .build();
+    let public_key = server.peers[2]
+        .public_key()
+        .to_string()
+        .replace('+', "-")
+        .replace('/', "_")
+        .replace('=', "");
+
+    let secret = KubernetesManifest::new("clients")
+        .add_clients(&server)
+        .unwrap()
+        .secret();
+    let entries = data_entries(&secret);
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].0, "Alice-s-Laptop.conf");
+    assert_eq!(entries[2].0, format!("{public_key}.conf"));
+
+    let phone = server.peers[1].clone();
+    let server = server.to_builder().add_peer(phone).build();
+    assert!(matches!(
+        KubernetesManifest::new("clients").add_clients(&server),
+        Err(WireguardError::InvalidValue(_))
+    ));
+}
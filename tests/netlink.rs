@@ -0,0 +1,155 @@
+#![cfg(all(feature = "netlink", target_os = "linux"))]
+
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+const FAMILY_ID: u16 = 0x1b;
+
+fn interface() -> Interface {
+    let first = PeerBuilder::new()
+        .endpoint("192.0.2.2:51820".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .add_allowed_ip(as_ipnet!("192.168.0.0/24"))
+        .preshared_key(PresharedKey::random())
+        .persistent_keepalive(25)
+        .build();
+    let second = PeerBuilder::new()
+        .endpoint("[2001:db8::1]:51821".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .build();
+
+    InterfaceBuilder::new()
+        .listen_port(51820)
+        .fwmark(0xca6c)
+        .add_peer(first)
+        .add_peer(second)
+        .build()
+}
+
+/// Peers, as they're returned by device: only public keys are known.
+fn device_peers(interface: &Interface) -> Vec<Peer> {
+    interface
+        .peers
+        .iter()
+        .map(|peer| peer.to_builder().public_key(peer.public_key()).build())
+        .collect()
+}
+
+#[test]
+fn netlink_set_device_round_trip() {
+    let interface = interface();
+
+    let messages = WireguardNetlink::set_device_messages(FAMILY_ID, 1, "wg0", &interface).unwrap();
+    assert_eq!(messages.len(), 1);
+
+    let parsed = WireguardNetlink::parse_device(&messages).unwrap();
+
+    assert_eq!(parsed.private_key, interface.private_key);
+    assert_eq!(parsed.listen_port, Some(51820));
+    assert_eq!(parsed.fwmark, Some(0xca6c));
+    assert_eq!(parsed.peers, device_peers(&interface));
+}
+
+#[test]
+fn netlink_set_device_header() {
+    let messages =
+        WireguardNetlink::set_device_messages(FAMILY_ID, 7, "wg0", &interface()).unwrap();
+    let message = &messages[0];
+
+    // nlmsghdr
+    assert_eq!(
+        u32::from_ne_bytes(message[0..4].try_into().unwrap()) as usize,
+        message.len()
+    );
+    assert_eq!(u16::from_ne_bytes([message[4], message[5]]), FAMILY_ID);
+    assert_eq!(u16::from_ne_bytes([message[6], message[7]]), 1 | 4); // REQUEST | ACK
+    assert_eq!(u32::from_ne_bytes(message[8..12].try_into().unwrap()), 7);
+
+    // genlmsghdr: WG_CMD_SET_DEVICE, version 1
+    assert_eq!(&message[16..20], &[1, 1, 0, 0]);
+
+    // WGDEVICE_A_IFNAME
+    assert_eq!(u16::from_ne_bytes([message[20], message[21]]), 8);
+    assert_eq!(u16::from_ne_bytes([message[22], message[23]]), 2);
+    assert_eq!(&message[24..28], b"wg0\0");
+}
+
+#[test]
+fn netlink_get_device_message() {
+    let message = WireguardNetlink::get_device_message(FAMILY_ID, 3, "wg0").unwrap();
+
+    assert_eq!(message.len(), 28);
+    assert_eq!(u16::from_ne_bytes([message[6], message[7]]), 1 | 0x300); // REQUEST | DUMP
+    assert_eq!(&message[16..20], &[0, 1, 0, 0]);
+    assert_eq!(&message[24..28], b"wg0\0");
+}
+
+#[test]
+fn netlink_split_peers() {
+    let mut builder = InterfaceBuilder::new();
+    for index in 0..200u32 {
+        let address = std::net::Ipv4Addr::from(0x0a00_0000 + index);
+        builder = builder.add_peer(
+            PeerBuilder::new()
+                .add_allowed_ip(Ipv4Net::new(address, 32).unwrap())
+                .build(),
+        );
+    }
+    let big_peer = PeerBuilder::new()
+        .set_allowed_ips(
+            (0..1000u32)
+                .map(|index| Ipv4Net::new(std::net::Ipv4Addr::from(0xc0a8_0000 + index), 32))
+                .collect::<Result<_, _>>()
+                .unwrap(),
+        )
+        .build();
+    let interface = builder.add_peer(big_peer).build();
+
+    let messages = WireguardNetlink::set_device_messages(FAMILY_ID, 1, "wg0", &interface).unwrap();
+    assert!(messages.len() > 1);
+    assert!(messages.iter().all(|message| message.len() <= 8192));
+
+    let parsed = WireguardNetlink::parse_device(&messages).unwrap();
+    assert_eq!(parsed.peers, device_peers(&interface));
+}
+
+#[test]
+fn netlink_parse_errors() {
+    let mut messages =
+        WireguardNetlink::set_device_messages(FAMILY_ID, 1, "wg0", &interface()).unwrap();
+    messages[0].truncate(40);
+
+    assert!(matches!(
+        WireguardNetlink::parse_device(&messages),
+        Err(WireguardError::InvalidValue(_))
+    ));
+
+    let interface = InterfaceBuilder::new()
+        .add_peer(
+            PeerBuilder::new()
+                .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+                .persistent_keepalive(70000)
+                .build(),
+        )
+        .build();
+
+    assert_eq!(
+        WireguardNetlink::set_device_messages(FAMILY_ID, 1, "wg0", &interface),
+        Err(WireguardError::InvalidValue(
+            "PersistentKeepalive".to_string()
+        ))
+    );
+
+    // device without private key
+    let message = WireguardNetlink::get_device_message(FAMILY_ID, 1, "wg0").unwrap();
+    assert_eq!(
+        WireguardNetlink::parse_device(&[message]).unwrap_err(),
+        WireguardError::NoPrivateKeyProvided
+    );
+
+    // attribute doesn't fit into netlink's length
+    assert!(matches!(
+        WireguardNetlink::get_device_message(FAMILY_ID, 1, &"a".repeat(70000)),
+        Err(WireguardError::InvalidValue(_))
+    ));
+}
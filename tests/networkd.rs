@@ -0,0 +1,180 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+fn get_example_data() -> (Interface, Peer, Peer) {
+    let laptop = PeerBuilder::new()
+        .name("Laptop".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(PresharedKey::random())
+        .build();
+    let site = PeerBuilder::new()
+        .endpoint("site.example.com:51820".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .add_allowed_ip(as_ipnet!("192.168.1.0/24"))
+        .persistent_keepalive(25)
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .fwmark(51820)
+        .mtu(1420)
+        .add_dns("1.1.1.1".to_string())
+        .add_peer(laptop.clone())
+        .add_peer(site.clone())
+        .build();
+
+    (interface, laptop, site)
+}
+
+#[test]
+fn networkd_netdev() {
+    let (interface, laptop, site) = get_example_data();
+
+    assert_eq!(
+        interface.to_networkd("wg0").netdev(),
+        format!(
+            "[NetDev]
+Name=wg0
+Kind=wireguard
+MTUBytes=1420
+
+[WireGuard]
+PrivateKey={}
+ListenPort=51820
+FirewallMark=51820
+
+[WireGuardPeer]
+# Name = Laptop
+PublicKey={}
+PresharedKey={}
+AllowedIPs=10.0.0.2/32
+
+[WireGuardPeer]
+PublicKey={}
+AllowedIPs=10.0.0.3/32
+AllowedIPs=192.168.1.0/24
+Endpoint=site.example.com:51820
+PersistentKeepalive=25
+",
+            interface.private_key,
+            laptop.public_key(),
+            laptop.preshared_key.unwrap(),
+            site.public_key()
+        )
+    );
+}
+
+#[test]
+fn networkd_key_files() {
+    let (interface, laptop, _) = get_example_data();
+
+    let netdev = interface
+        .to_networkd("wg0")
+        .private_key_file("/etc/systemd/network/wg0.key".to_string())
+        .preshared_key_file(
+            laptop.public_key(),
+            "/etc/systemd/network/laptop.psk".to_string(),
+        )
+        .netdev();
+
+    assert!(netdev.contains("PrivateKeyFile=/etc/systemd/network/wg0.key\n"));
+    assert!(netdev.contains("PresharedKeyFile=/etc/systemd/network/laptop.psk\n"));
+    assert!(!netdev.contains("PrivateKey="));
+    assert!(!netdev.contains("PresharedKey="));
+}
+
+#[test]
+fn networkd_network() {
+    let (interface, _, _) = get_example_data();
+
+    assert_eq!(
+        interface.to_networkd("wg0").network(),
+        "[Match]
+Name=wg0
+
+[Network]
+Address=10.0.0.1/24
+DNS=1.1.1.1
+
+[Route]
+Destination=192.168.1.0/24
+"
+    );
+}
+
+#[test]
+fn networkd_full_tunnel() {
+    let server = PeerBuilder::new()
+        .endpoint("vpn.example.com:51820".to_string())
+        .add_allowed_ip(as_ipnet!("0.0.0.0/0"))
+        .add_allowed_ip(as_ipnet!("192.168.1.0/24"))
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.2/32"))
+        .add_peer(server)
+        .build();
+
+    let config = interface.to_networkd("wg0");
+
+    let netdev = config.netdev();
+    assert!(netdev.contains("FirewallMark=51820\n"));
+    assert!(netdev.contains("AllowedIPs=0.0.0.0/0\n"));
+    assert_eq!(
+        config.network(),
+        "[Match]
+Name=wg0
+
+[Network]
+Address=10.0.0.2/32
+
+[Route]
+Destination=192.168.1.0/24
+
+[Route]
+Destination=0.0.0.0/0
+Table=51820
+
+[RoutingPolicyRule]
+Table=main
+SuppressPrefixLength=0
+Priority=32764
+
+[RoutingPolicyRule]
+FirewallMark=51820
+InvertRule=true
+Table=51820
+Priority=32765
+"
+    );
+
+    // interface's fwmark is used, if it's set
+    let config = interface.to_builder().fwmark(0x1234).build();
+    let config = config.to_networkd("wg0").default_route_table(Some(1000));
+    assert!(config.netdev().contains("FirewallMark=4660\n"));
+    assert!(config
+        .network()
+        .contains("FirewallMark=4660\nInvertRule=true\nTable=1000\n"));
+
+    // default route can be skipped explicitly
+    let config = interface.to_networkd("wg0").default_route_table(None);
+    assert!(!config.netdev().contains("FirewallMark="));
+    assert!(!config.network().contains("0.0.0.0/0"));
+    assert!(!config.network().contains("[RoutingPolicyRule]"));
+}
+
+#[test]
+fn networkd_netdev_ignores_endpoint() {
+    let (interface, _, _) = get_example_data();
+    let interface = interface
+        .to_builder()
+        .endpoint("vpn.example.com\nPrivateKeyFile=/tmp/key".to_string())
+        .build();
+
+    let netdev = interface.to_networkd("wg0").netdev();
+
+    assert!(!netdev.contains("Description="));
+    assert!(!netdev.contains("vpn.example.com"));
+    assert!(!netdev.contains("PrivateKeyFile="));
+}
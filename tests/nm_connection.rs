@@ -0,0 +1,182 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+fn get_example_data() -> (Interface, Peer) {
+    let server = PeerBuilder::new()
+        .endpoint("vpn.example.com:51820".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.0/24"))
+        .add_allowed_ip(as_ipnet!("192.168.1.0/24"))
+        .preshared_key(PresharedKey::random())
+        .persistent_keepalive(25)
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.2/24"))
+        .listen_port(51820)
+        .mtu(1420)
+        .set_dns(vec!["10.0.0.1".to_string(), "corp.example.com".to_string()])
+        .add_peer(server.clone())
+        .build();
+
+    (interface, server)
+}
+
+#[test]
+fn nmconnection_export() {
+    let (interface, server) = get_example_data();
+
+    assert_eq!(
+        interface
+            .to_nmconnection("wg0")
+            .uuid("2f0b4b4e-8d8f-4a4c-9b5e-4ad0d6a4c1f1".to_string())
+            .to_string(),
+        format!(
+            "[connection]
+id=wg0
+uuid=2f0b4b4e-8d8f-4a4c-9b5e-4ad0d6a4c1f1
+type=wireguard
+interface-name=wg0
+
+[wireguard]
+private-key={}
+listen-port=51820
+mtu=1420
+
+[wireguard-peer.{}]
+endpoint=vpn.example.com:51820
+preshared-key={}
+preshared-key-flags=0
+persistent-keepalive=25
+allowed-ips=10.0.0.0/24;192.168.1.0/24;
+
+[ipv4]
+address1=10.0.0.2/24
+dns=10.0.0.1;
+dns-search=corp.example.com;
+method=manual
+
+[ipv6]
+addr-gen-mode=default
+method=disabled
+",
+            interface.private_key,
+            server.public_key(),
+            server.preshared_key.unwrap()
+        )
+    );
+}
+
+#[test]
+fn nmconnection_roundtrip() {
+    let (interface, _) = get_example_data();
+
+    let imported =
+        Interface::from_nmconnection(&interface.to_nmconnection("wg0").to_string()).unwrap();
+
+    assert_eq!(imported.to_string(), interface.to_string());
+}
+
+#[test]
+fn nmconnection_keyfile_roundtrip() {
+    let keyfile = "\
+[connection]
+id=wg0
+type=wireguard
+interface-name=wg0
+
+[wireguard]
+private-key=yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+
+[wireguard-peer.xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=]
+endpoint=192.0.2.1:51820
+allowed-ips=0.0.0.0/0;
+
+[ipv4]
+address1=10.0.0.2/24
+dns=10.0.0.1;1.1.1.1;
+dns-search=corp.example.com;example.com;
+method=manual
+
+[ipv6]
+addr-gen-mode=default
+method=disabled
+";
+
+    let imported = Interface::from_nmconnection(keyfile).unwrap();
+    assert_eq!(
+        imported.dns,
+        vec!["10.0.0.1", "1.1.1.1", "corp.example.com", "example.com"]
+    );
+    assert_eq!(imported.to_nmconnection("wg0").to_string(), keyfile);
+}
+
+#[test]
+fn nmconnection_import() {
+    let imported = Interface::from_nmconnection(
+        "\
+[connection]
+id=Office
+type=wireguard
+interface-name=wg-office
+
+[wireguard]
+private-key=yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+fwmark=0
+
+[wireguard-peer.xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=]
+endpoint=192.0.2.1:51820
+preshared-key=FpCyhws9cxwWoV4xELtfJvjJN+zQVRPISllRWgeopVE=
+preshared-key-flags=4
+persistent-keepalive=0
+allowed-ips=0.0.0.0/0;::/0;
+
+[ipv4]
+address2=10.1.0.2/16
+address1=10.0.0.2/24,10.0.0.1
+dns=1.1.1.1;1.0.0.1;
+method=manual
+",
+    )
+    .unwrap();
+
+    assert_eq!(imported.address, as_ipnet!("10.0.0.2/24"));
+    assert_eq!(imported.dns, vec!["1.1.1.1", "1.0.0.1"]);
+    assert_eq!(imported.fwmark, None);
+    assert_eq!(imported.mtu, None);
+
+    let peer = &imported.peers[0];
+    assert_eq!(
+        peer.public_key().to_string(),
+        "xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg="
+    );
+    assert_eq!(peer.endpoint, Some("192.0.2.1:51820".to_string()));
+    assert_eq!(peer.allowed_ips, vec![as_ipnet!("0.0.0.0/0")]);
+    assert_eq!(peer.preshared_key, None);
+    assert_eq!(peer.persistent_keepalive, None);
+}
+
+#[test]
+fn nmconnection_import_errors() {
+    assert_eq!(
+        Interface::from_nmconnection("[connection]\nid=wg0\n").unwrap_err(),
+        WireguardError::MissingSection("wireguard".to_string())
+    );
+    assert_eq!(
+        Interface::from_nmconnection("[wireguard]\nlisten-port=1\n").unwrap_err(),
+        WireguardError::NoPrivateKeyProvided
+    );
+    assert_eq!(
+        Interface::from_nmconnection(
+            "[wireguard]\nprivate-key=yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\n"
+        )
+        .unwrap_err(),
+        WireguardError::NoAddressProvided
+    );
+    assert_eq!(
+        Interface::from_nmconnection(
+            "[wireguard]\nprivate-key=yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\nmtu=big\n"
+        )
+        .unwrap_err(),
+        WireguardError::InvalidValue("mtu".to_string())
+    );
+}
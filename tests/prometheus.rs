@@ -0,0 +1,79 @@
+#![cfg(feature = "prometheus")]
+
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+use std::time::{Duration, SystemTime};
+
+const DUMP: &str = "\
+yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\tHIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=\t51820\toff
+xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t192.0.2.2:51820\t10.0.0.2/32\t1700000000\t2224\t38333\t25
+TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=\t(none)\t192.0.2.3:51820\t10.0.0.3/32\t1699999000\t100\t200\toff
+";
+
+#[test]
+fn prometheus_metrics() {
+    let status: InterfaceStatus = DUMP.parse().unwrap();
+
+    let laptop_key = "xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=";
+    let phone_key = "TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=";
+    let laptop = PeerBuilder::new()
+        .name("Alice's \"laptop\"".to_string())
+        .public_key(PublicKey::try_from(laptop_key.to_string()).unwrap())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .add_allowed_ip(as_ipnet!("10.0.1.0/24"))
+        .build();
+    let phone = PeerBuilder::new()
+        .public_key(PublicKey::try_from(phone_key.to_string()).unwrap())
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .build();
+    let offline = PeerBuilder::new()
+        .name("Offline".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+        .build();
+    let offline_key = offline.public_key();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .set_peers(vec![laptop, phone, offline])
+        .build();
+
+    let metrics = PrometheusMetrics::new(&interface, &status)
+        .interface_name("wg0".to_string())
+        .handshake_threshold(Duration::from_secs(300))
+        .now(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_042))
+        .to_string();
+
+    let laptop_labels = format!(
+        "interface=\"wg0\",public_key=\"{laptop_key}\",name=\"Alice's \\\"laptop\\\"\",allowed_ips=\"10.0.0.2/32,10.0.1.0/24\""
+    );
+    let phone_labels = format!(
+        "interface=\"wg0\",public_key=\"{phone_key}\",name=\"\",allowed_ips=\"10.0.0.3/32\""
+    );
+    let offline_labels = format!(
+        "interface=\"wg0\",public_key=\"{offline_key}\",name=\"Offline\",allowed_ips=\"10.0.0.4/32\""
+    );
+
+    assert_eq!(
+        metrics,
+        format!(
+            "# HELP wireguard_peer_up Whether the latest handshake is newer than threshold.\n\
+             # TYPE wireguard_peer_up gauge\n\
+             wireguard_peer_up{{{laptop_labels}}} 1\n\
+             wireguard_peer_up{{{phone_labels}}} 0\n\
+             wireguard_peer_up{{{offline_labels}}} 0\n\
+             # HELP wireguard_peer_last_handshake_age_seconds Seconds since the latest handshake.\n\
+             # TYPE wireguard_peer_last_handshake_age_seconds gauge\n\
+             wireguard_peer_last_handshake_age_seconds{{{laptop_labels}}} 42\n\
+             wireguard_peer_last_handshake_age_seconds{{{phone_labels}}} 1042\n\
+             # HELP wireguard_peer_receive_bytes_total Bytes received from the peer.\n\
+             # TYPE wireguard_peer_receive_bytes_total counter\n\
+             wireguard_peer_receive_bytes_total{{{laptop_labels}}} 2224\n\
+             wireguard_peer_receive_bytes_total{{{phone_labels}}} 100\n\
+             # HELP wireguard_peer_transmit_bytes_total Bytes transmitted to the peer.\n\
+             # TYPE wireguard_peer_transmit_bytes_total counter\n\
+             wireguard_peer_transmit_bytes_total{{{laptop_labels}}} 38333\n\
+             wireguard_peer_transmit_bytes_total{{{phone_labels}}} 200\n"
+        )
+    );
+}
@@ -0,0 +1,92 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+#[test]
+fn routeros_script() {
+    let laptop = PeerBuilder::new()
+        .name("Laptop \"home\"".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(PresharedKey::random())
+        .build();
+    let site = PeerBuilder::new()
+        .endpoint("[2001:db8::1]:51821".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .add_allowed_ip(as_ipnet!("192.168.1.0/24"))
+        .persistent_keepalive(25)
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .mtu(1420)
+        .add_peer(laptop.clone())
+        .add_peer(site.clone())
+        .build();
+
+    assert_eq!(
+        interface.to_routeros("wg-branch").unwrap(),
+        format!(
+            "\
+/interface wireguard add name=\"wg-branch\" listen-port=51820 mtu=1420 private-key=\"{}\"
+/interface wireguard peers add interface=\"wg-branch\" public-key=\"{}\" allowed-address=10.0.0.2/32 preshared-key=\"{}\" comment=\"Laptop \\\"home\\\"\"
+/interface wireguard peers add interface=\"wg-branch\" public-key=\"{}\" allowed-address=10.0.0.3/32,192.168.1.0/24 endpoint-address=\"2001:db8::1\" endpoint-port=51821 persistent-keepalive=25s
+/ip address add address=10.0.0.1/24 interface=\"wg-branch\"
+",
+            interface.private_key,
+            laptop.public_key(),
+            laptop.preshared_key.unwrap(),
+            site.public_key(),
+        )
+    );
+}
+
+#[test]
+fn routeros_escape_control_characters() {
+    let peer = PeerBuilder::new()
+        .name("Laptop\n/system reset-configuration\r\t\u{7}".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .build();
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .add_peer(peer)
+        .build();
+
+    let script = interface.to_routeros("wg0").unwrap();
+
+    assert_eq!(script.lines().count(), 3);
+    assert!(script.contains(" comment=\"Laptop\\n/system reset-configuration\\r\\t\\07\"\n"));
+}
+
+#[test]
+fn routeros_endpoint_parts() {
+    let peer = |endpoint: &str| {
+        PeerBuilder::new()
+            .endpoint(endpoint.to_string())
+            .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+            .build()
+    };
+
+    assert_eq!(
+        peer("vpn.example.com:51820").endpoint_parts(),
+        Ok(Some(("vpn.example.com", 51820)))
+    );
+    assert_eq!(
+        peer("192.0.2.1:1").endpoint_parts(),
+        Ok(Some(("192.0.2.1", 1)))
+    );
+
+    for endpoint in [
+        "vpn.example.com",
+        ":51820",
+        "[2001:db8::1:51820",
+        "host:port",
+    ] {
+        assert_eq!(
+            peer(endpoint).endpoint_parts(),
+            Err(WireguardError::InvalidValue("Endpoint".to_string()))
+        );
+
+        let interface = InterfaceBuilder::new().add_peer(peer(endpoint)).build();
+        assert!(interface.to_routeros("wg0").is_err());
+    }
+}
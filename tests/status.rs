@@ -0,0 +1,100 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+use std::time::{Duration, SystemTime};
+
+const DUMP: &str = "\
+yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\tHIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=\t51820\t0xca6c
+xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t192.0.2.2:51820\t10.0.0.2/32,fd00::2/128\t1700000000\t2224\t38333\t25
+TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=\tFpCyhws9cxwWoV4xELtfJvjJN+zQVRPISllRWgeopVE=\t(none)\t10.0.0.3/32,10.0.1.0/24\t0\t0\t0\toff
+";
+
+#[test]
+fn status_parse_dump() {
+    let status = InterfaceStatus::parse_dump(DUMP).unwrap();
+
+    assert_eq!(
+        status.public_key,
+        Some(
+            PublicKey::try_from("HIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=".to_string())
+                .unwrap()
+        )
+    );
+    assert_eq!(status.listen_port, 51820);
+    assert_eq!(status.fwmark, Some(0xca6c));
+    assert_eq!(status.peers.len(), 2);
+
+    let peer = &status.peers[0];
+    assert_eq!(peer.preshared_key, None);
+    assert_eq!(peer.endpoint, Some("192.0.2.2:51820".parse().unwrap()));
+    assert_eq!(peer.allowed_ips, vec![as_ipnet!("10.0.0.2/32")]);
+    assert_eq!(
+        peer.latest_handshake,
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+    assert_eq!(peer.transfer_rx, 2224);
+    assert_eq!(peer.transfer_tx, 38333);
+    assert_eq!(peer.persistent_keepalive, Some(25));
+
+    let peer = &status.peers[1];
+    assert!(peer.preshared_key.is_some());
+    assert_eq!(peer.endpoint, None);
+    assert_eq!(
+        peer.allowed_ips,
+        vec![as_ipnet!("10.0.0.3/32"), as_ipnet!("10.0.1.0/24")]
+    );
+    assert_eq!(peer.latest_handshake, None);
+    assert_eq!(peer.persistent_keepalive, None);
+}
+
+#[test]
+fn status_parse_dump_errors() {
+    assert_eq!(
+        InterfaceStatus::parse_dump(""),
+        Err(WireguardError::InvalidLine(1))
+    );
+    assert_eq!(
+        InterfaceStatus::parse_dump("(none)\t(none)\t0\toff\nkey\t(none)\n"),
+        Err(WireguardError::InvalidLine(2))
+    );
+    assert_eq!(
+        InterfaceStatus::parse_dump("(none)\t(none)\tport\toff\n"),
+        Err(WireguardError::InvalidValue("listen-port".to_string()))
+    );
+    assert_eq!(
+        InterfaceStatus::parse_dump(
+            "(none)\t(none)\t51820\toff\nxTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t(none)\t(none)\t18446744073709551615\t0\t0\toff\n"
+        ),
+        Err(WireguardError::InvalidValue("latest-handshake".to_string()))
+    );
+}
+
+#[test]
+fn status_join_peers() {
+    let status: InterfaceStatus = DUMP.parse().unwrap();
+
+    let laptop = PeerBuilder::new()
+        .name("Laptop".to_string())
+        .public_key(
+            PublicKey::try_from("xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=".to_string())
+                .unwrap(),
+        )
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .build();
+    let offline = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .add_peer(laptop.clone())
+        .add_peer(offline.clone())
+        .build();
+
+    assert_eq!(laptop.status(&status), Some(&status.peers[0]));
+
+    let peers = interface.peers_status(&status);
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[0], (&laptop, Some(&status.peers[0])));
+    assert_eq!(peers[1], (&offline, None));
+}
@@ -0,0 +1,404 @@
+#![cfg(all(feature = "uapi", unix))]
+
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const PRIVATE_KEY: &str = "e84b5a6d2717c1003a13b431570353dbaca9146cf150c5f8575680feba52027a";
+const PUBLIC_KEY: &str = "b85996fecc9c7f1fc6d2572a76eda11d59bcd20be8e543b15ce4bd85a8e75a33";
+const PRESHARED_KEY: &str = "188515093e952f5f22e865cef3012e72f8b5f0b598ac0309d5dacce3b70fcf52";
+
+const GET_RESPONSE: &str = "\
+private_key=e84b5a6d2717c1003a13b431570353dbaca9146cf150c5f8575680feba52027a
+listen_port=12912
+public_key=b85996fecc9c7f1fc6d2572a76eda11d59bcd20be8e543b15ce4bd85a8e75a33
+preshared_key=188515093e952f5f22e865cef3012e72f8b5f0b598ac0309d5dacce3b70fcf52
+allowed_ip=192.168.4.4/32
+endpoint=[abcd:23::33%2]:51820
+last_handshake_time_sec=1700000000
+last_handshake_time_nsec=500
+tx_bytes=38333
+rx_bytes=2224
+persistent_keepalive_interval=25
+protocol_version=1
+public_key=58402e695ba1772b1cc9309755f043251ea77fdcf10fbe63989ceb7e19321376
+preshared_key=0000000000000000000000000000000000000000000000000000000000000000
+allowed_ip=192.168.4.10/32
+allowed_ip=fd00::10/128
+endpoint=182.122.22.19:3233
+last_handshake_time_sec=0
+last_handshake_time_nsec=0
+tx_bytes=0
+rx_bytes=0
+persistent_keepalive_interval=0
+protocol_version=1
+errno=0
+
+";
+
+/// Spawns UAPI socket stand-in, that replies `response` to every request and returns requests.
+fn spawn_device(name: &str, response: &'static str) -> (PathBuf, thread::JoinHandle<Vec<String>>) {
+    let path =
+        std::env::temp_dir().join(format!("wireguard-conf-{}-{name}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut requests = Vec::new();
+
+        loop {
+            let mut request = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    return requests;
+                }
+                request.push_str(&line);
+                if line == "\n" {
+                    break;
+                }
+            }
+
+            requests.push(request);
+            writer.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    (path, handle)
+}
+
+#[test]
+fn uapi_parse() {
+    let device = UapiDevice::parse(GET_RESPONSE).unwrap();
+
+    assert_eq!(
+        device.private_key.unwrap().to_bytes(),
+        PrivateKey::try_from("6EtabScXwQA6E7QxVwNT26ypFGzxUMX4V1aA/rpSAno=".to_string())
+            .unwrap()
+            .to_bytes()
+    );
+    assert_eq!(device.listen_port, Some(12912));
+    assert_eq!(device.fwmark, None);
+    assert_eq!(device.peers.len(), 2);
+
+    let peer = &device.peers[0];
+    assert_eq!(
+        peer.public_key,
+        PublicKey::try_from("uFmW/sycfx/G0lcqdu2hHVm80gvo5UOxXOS9hajnWjM=".to_string()).unwrap()
+    );
+    assert!(peer.preshared_key.is_some());
+    assert_eq!(peer.allowed_ips, vec![as_ipnet!("192.168.4.4/32")]);
+    assert_eq!(
+        peer.endpoint,
+        Some("[abcd:23::33%2]:51820".parse().unwrap())
+    );
+    assert_eq!(
+        peer.last_handshake,
+        Some(SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 500))
+    );
+    assert_eq!(peer.tx_bytes, 38333);
+    assert_eq!(peer.rx_bytes, 2224);
+    assert_eq!(peer.persistent_keepalive_interval, Some(25));
+
+    let peer = &device.peers[1];
+    assert_eq!(peer.preshared_key, None);
+    assert_eq!(peer.allowed_ips, vec![as_ipnet!("192.168.4.10/32")]);
+    assert_eq!(peer.last_handshake, None);
+    assert_eq!(peer.persistent_keepalive_interval, None);
+}
+
+#[test]
+fn uapi_parse_errors() {
+    assert_eq!(
+        UapiDevice::parse("errno=22\n\n"),
+        Err(WireguardError::UapiErrno(22))
+    );
+    assert_eq!(
+        UapiDevice::parse("listen_port=port\nerrno=0\n\n"),
+        Err(WireguardError::InvalidValue("listen_port".to_string()))
+    );
+    assert_eq!(
+        UapiDevice::parse("private_key=abcd\n"),
+        Err(WireguardError::InvalidValue("private_key".to_string()))
+    );
+    assert_eq!(
+        UapiDevice::parse(&format!(
+            "public_key={PUBLIC_KEY}\nlast_handshake_time_sec=18446744073709551615\nlast_handshake_time_nsec=0\n\n"
+        )),
+        Err(WireguardError::InvalidValue(
+            "last_handshake_time_sec".to_string()
+        ))
+    );
+    assert_eq!(
+        UapiDevice::parse(&format!(
+            "public_key={PUBLIC_KEY}\nlast_handshake_time_sec=1\nlast_handshake_time_nsec=1000000000\n\n"
+        )),
+        Err(WireguardError::InvalidValue(
+            "last_handshake_time_nsec".to_string()
+        ))
+    );
+}
+
+#[test]
+fn uapi_parse_handshake_per_peer() {
+    let response = format!(
+        "public_key={PUBLIC_KEY}\n\
+         last_handshake_time_sec=1700000000\n\
+         last_handshake_time_nsec=0\n\
+         public_key=58402e695ba1772b1cc9309755f043251ea77fdcf10fbe63989ceb7e19321376\n\
+         last_handshake_time_nsec=0\n\
+         errno=0\n\n"
+    );
+    let device = UapiDevice::parse(&response).unwrap();
+
+    assert_eq!(
+        device.peers[0].last_handshake,
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+    assert_eq!(device.peers[1].last_handshake, None);
+}
+
+#[test]
+fn uapi_set_operation() {
+    let private_key = PrivateKey::from(hex(PRIVATE_KEY));
+    let public_key = PublicKey::from(hex(PUBLIC_KEY));
+    let preshared_key = PresharedKey::from(hex(PRESHARED_KEY));
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .private_key(private_key)
+        .listen_port(12912)
+        .add_peer(
+            PeerBuilder::new()
+                .public_key(public_key)
+                .preshared_key(preshared_key)
+                .endpoint("192.0.2.1:51820".to_string())
+                .persistent_keepalive(25)
+                .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+                .add_allowed_ip(as_ipnet!("10.0.1.0/24"))
+                .build(),
+        )
+        .build();
+
+    assert_eq!(
+        interface.to_uapi().unwrap(),
+        format!(
+            "set=1\n\
+             private_key={PRIVATE_KEY}\n\
+             listen_port=12912\n\
+             fwmark=0\n\
+             replace_peers=true\n\
+             public_key={PUBLIC_KEY}\n\
+             preshared_key={PRESHARED_KEY}\n\
+             endpoint=192.0.2.1:51820\n\
+             persistent_keepalive_interval=25\n\
+             replace_allowed_ips=true\n\
+             allowed_ip=10.0.0.2/32\n\
+             allowed_ip=10.0.1.0/24\n\
+             \n"
+        )
+    );
+}
+
+#[test]
+fn uapi_client() {
+    let (path, device) = spawn_device("client", GET_RESPONSE);
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .build();
+
+    let mut client = UapiClient::connect(path.to_str().unwrap()).unwrap();
+    assert_eq!(client.get().unwrap().peers.len(), 2);
+    client.set(&interface).unwrap();
+    drop(client);
+
+    let requests = device.join().unwrap();
+    assert_eq!(
+        requests,
+        vec!["get=1\n\n".to_string(), interface.to_uapi().unwrap()]
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn uapi_client_resolves_endpoints() {
+    let (path, device) = spawn_device("resolve", "errno=0\n\n");
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .add_peer(
+            PeerBuilder::new()
+                .endpoint("localhost:51820".to_string())
+                .build(),
+        )
+        .build();
+    assert_eq!(
+        interface.to_uapi(),
+        Err(WireguardError::InvalidValue("Endpoint".to_string()))
+    );
+
+    let mut client = UapiClient::connect(path.to_str().unwrap()).unwrap();
+    client.set(&interface).unwrap();
+    drop(client);
+
+    let requests = device.join().unwrap();
+    let endpoint = interface.peers[0].resolve_endpoint().unwrap().unwrap();
+    assert!(requests[0].contains(&format!("\nendpoint={endpoint}\n")));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn uapi_client_keeps_buffered_response() {
+    let path = std::env::temp_dir().join(format!(
+        "wireguard-conf-{}-buffered.sock",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    // device answers first request with two responses at once and second request with nothing
+    let device = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        for response in ["errno=0\n\nerrno=1\n\n", ""] {
+            let mut line = String::new();
+            while line != "\n" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            writer.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    let mut client = UapiClient::connect(path.to_str().unwrap()).unwrap();
+    assert!(client.get().is_ok());
+    assert_eq!(client.get(), Err(WireguardError::UapiErrno(1)));
+    drop(client);
+
+    device.join().unwrap();
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn uapi_client_errno() {
+    let (path, device) = spawn_device("errno", "errno=1\n\n");
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .build();
+
+    let mut client = UapiClient::connect(path.to_str().unwrap()).unwrap();
+    assert_eq!(client.set(&interface), Err(WireguardError::UapiErrno(1)));
+    drop(client);
+
+    device.join().unwrap();
+    let _ = std::fs::remove_file(path);
+}
+
+fn hex(value: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).unwrap();
+    }
+
+    bytes
+}
+
+#[test]
+fn uapi_to_status() {
+    let device = UapiDevice::parse(GET_RESPONSE).unwrap();
+    let status = InterfaceStatus::from(device.clone());
+
+    assert_eq!(
+        status.public_key,
+        Some(PublicKey::from(&device.private_key.unwrap()))
+    );
+    assert_eq!(status.listen_port, 12912);
+    assert_eq!(status.peers.len(), 2);
+    assert_eq!(status.peers[0].transfer_rx, 2224);
+    assert_eq!(
+        status.peers[0].latest_handshake,
+        device.peers[0].last_handshake
+    );
+}
+
+#[cfg(feature = "amneziawg")]
+#[test]
+fn uapi_amnezia() {
+    let amnezia_settings = AmneziaSettings {
+        jc: 4,
+        jmin: 40,
+        jmax: 70,
+        s1: 50,
+        s2: 60,
+        s3: Some(30),
+        s4: None,
+        h1: MagicHeader::new(100, 199),
+        h2: 200.into(),
+        h3: 300.into(),
+        h4: 400.into(),
+        i1: Some("<b 0xc0ff><r 16>".parse().unwrap()),
+        i2: None,
+        i3: None,
+        i4: None,
+        i5: None,
+    };
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .private_key(PrivateKey::from(hex(PRIVATE_KEY)))
+        .amnezia_settings(amnezia_settings.clone())
+        .build();
+
+    let operation = interface.to_uapi().unwrap();
+    assert_eq!(
+        operation,
+        format!(
+            "set=1\n\
+             private_key={PRIVATE_KEY}\n\
+             fwmark=0\n\
+             jc=4\n\
+             jmin=40\n\
+             jmax=70\n\
+             s1=50\n\
+             s2=60\n\
+             s3=30\n\
+             h1=100-199\n\
+             h2=200\n\
+             h3=300\n\
+             h4=400\n\
+             i1=<b 0xc0ff><r 16>\n\
+             replace_peers=true\n\
+             \n"
+        )
+    );
+
+    let response = operation
+        .strip_prefix("set=1\n")
+        .unwrap()
+        .replace("replace_peers=true\n", "listen_port=51820\nerrno=0\n");
+    let device = UapiDevice::parse(&response).unwrap();
+    assert_eq!(device.amnezia_settings, Some(amnezia_settings));
+
+    assert_eq!(
+        UapiDevice::parse(GET_RESPONSE).unwrap().amnezia_settings,
+        None
+    );
+    assert_eq!(
+        UapiDevice::parse("jc=4\njmin=40\n").unwrap_err(),
+        WireguardError::InvalidAmneziaSetting("Jmax".to_string())
+    );
+}
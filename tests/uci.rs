@@ -0,0 +1,192 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+fn get_example_data() -> (Interface, Peer, Peer) {
+    let laptop = PeerBuilder::new()
+        .description("Bob's laptop".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(PresharedKey::random())
+        .build();
+    let site = PeerBuilder::new()
+        .endpoint("[2001:db8::1]:51821".to_string())
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .add_allowed_ip(as_ipnet!("192.168.1.0/24"))
+        .persistent_keepalive(25)
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .mtu(1420)
+        .add_dns("1.1.1.1".to_string())
+        .add_peer(laptop.clone())
+        .add_peer(site.clone())
+        .build();
+
+    (interface, laptop, site)
+}
+
+#[test]
+fn uci_export() {
+    let (interface, laptop, site) = get_example_data();
+
+    assert_eq!(
+        interface.to_uci("wg0").route_allowed_ips(false).to_string(),
+        format!(
+            "\
+config interface 'wg0'
+\toption proto 'wireguard'
+\toption private_key '{}'
+\toption listen_port '51820'
+\toption mtu '1420'
+\tlist addresses '10.0.0.1/24'
+\tlist dns '1.1.1.1'
+
+config wireguard_wg0
+\toption description 'Bob'\\''s laptop'
+\toption public_key '{}'
+\toption preshared_key '{}'
+\toption route_allowed_ips '0'
+\tlist allowed_ips '10.0.0.2/32'
+
+config wireguard_wg0
+\toption public_key '{}'
+\toption endpoint_host '2001:db8::1'
+\toption endpoint_port '51821'
+\toption persistent_keepalive '25'
+\toption route_allowed_ips '0'
+\tlist allowed_ips '10.0.0.3/32'
+\tlist allowed_ips '192.168.1.0/24'
+",
+            interface.private_key,
+            laptop.public_key(),
+            laptop.preshared_key.unwrap(),
+            site.public_key(),
+        )
+    );
+}
+
+#[test]
+fn uci_roundtrip() {
+    let (interface, _, _) = get_example_data();
+
+    let imported = Interface::from_uci(&interface.to_uci("wg0").to_string(), "wg0").unwrap();
+
+    assert_eq!(imported.to_string(), interface.to_string());
+    assert_eq!(
+        imported.peers[0].description,
+        Some("Bob's laptop".to_string())
+    );
+}
+
+#[test]
+fn uci_import() {
+    let config = "\
+config interface 'loopback'
+\toption device 'lo'
+\toption proto 'static'
+
+# office tunnel
+config interface 'wg_office'
+\toption proto 'wireguard'
+\toption private_key 'yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk='
+\toption addresses 'fd00::2/64 10.0.0.2/24'
+\toption fwmark '0x10'
+
+config wireguard_wg_office
+\toption description \"Office router\"
+\toption public_key xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=
+\toption endpoint_host 'office.example.com'
+\tlist allowed_ips '0.0.0.0/0'
+\tlist allowed_ips '::/0'
+
+config wireguard_wg_office
+\toption disabled '1'
+\toption public_key 'HIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw='
+";
+
+    let imported = Interface::from_uci(config, "wg_office").unwrap();
+
+    assert_eq!(imported.address, as_ipnet!("10.0.0.2/24"));
+    assert_eq!(imported.fwmark, Some(0x10));
+    assert_eq!(imported.peers.len(), 1);
+
+    let peer = &imported.peers[0];
+    assert_eq!(peer.name, None);
+    assert_eq!(peer.description, Some("Office router".to_string()));
+    assert_eq!(peer.endpoint, Some("office.example.com:51820".to_string()));
+    assert_eq!(peer.allowed_ips, vec![as_ipnet!("0.0.0.0/0")]);
+}
+
+#[test]
+fn uci_import_export_output() {
+    // `uci export network` output
+    let config = "\
+package network
+
+config interface 'loopback'
+\toption device 'lo'
+\toption proto 'static'
+\toption ipaddr '127.0.0.1'
+\toption netmask '255.0.0.0'
+
+config globals 'globals'
+\toption ula_prefix 'fd12:3456:789a::/48'
+
+config interface 'wg0'
+\toption proto 'wireguard'
+\toption private_key 'yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk='
+\toption listen_port '51820'
+\tlist addresses '10.0.0.1/24'
+
+config wireguard_wg0 'wgclient'
+\toption public_key 'xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg='
+\toption description 'Laptop'
+\toption route_allowed_ips '1'
+\tlist allowed_ips '10.0.0.2/32'
+
+";
+
+    let imported = Interface::from_uci(config, "wg0").unwrap();
+
+    assert_eq!(imported.address, as_ipnet!("10.0.0.1/24"));
+    assert_eq!(imported.listen_port, Some(51820));
+    assert_eq!(imported.peers.len(), 1);
+    assert_eq!(imported.peers[0].description, Some("Laptop".to_string()));
+    assert_eq!(
+        imported.peers[0].allowed_ips,
+        vec![as_ipnet!("10.0.0.2/32")]
+    );
+}
+
+#[test]
+fn uci_import_errors() {
+    let interface = "config interface 'wg0'\n\toption proto 'wireguard'\n";
+
+    assert_eq!(
+        Interface::from_uci(interface, "wg1").unwrap_err(),
+        WireguardError::MissingSection("interface 'wg1'".to_string())
+    );
+    assert_eq!(
+        Interface::from_uci(interface, "wg0").unwrap_err(),
+        WireguardError::NoPrivateKeyProvided
+    );
+    assert_eq!(
+        Interface::from_uci("option proto 'wireguard'\n", "wg0").unwrap_err(),
+        WireguardError::InvalidLine(1)
+    );
+    assert_eq!(
+        Interface::from_uci("config interface 'wg0\n", "wg0").unwrap_err(),
+        WireguardError::InvalidLine(1)
+    );
+    assert_eq!(
+        Interface::from_uci(
+            &format!(
+                "{interface}\toption private_key 'yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk='\n"
+            ),
+            "wg0"
+        )
+        .unwrap_err(),
+        WireguardError::NoAddressProvided
+    );
+}
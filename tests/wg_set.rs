@@ -0,0 +1,157 @@
+use ipnet::Ipv4Net;
+use wireguard_conf::{as_ipnet, prelude::*};
+
+fn get_example_data() -> (Interface, Peer, Peer) {
+    let laptop = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.2/32"))
+        .preshared_key(PresharedKey::random())
+        .build();
+    let phone = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.3/32"))
+        .persistent_keepalive(25)
+        .build();
+
+    let interface = InterfaceBuilder::new()
+        .address(as_ipnet!("10.0.0.1/24"))
+        .listen_port(51820)
+        .add_peer(laptop.clone())
+        .add_peer(phone.clone())
+        .build();
+
+    (interface, laptop, phone)
+}
+
+#[test]
+fn wg_set_no_changes() {
+    let (interface, _, _) = get_example_data();
+
+    let new = interface
+        .to_builder()
+        .add_dns("8.8.8.8".to_string())
+        .build();
+
+    assert_eq!(interface.wg_set_commands(&new), vec![]);
+}
+
+#[test]
+fn wg_set_interface() {
+    let (old, _, _) = get_example_data();
+    let private_key = PrivateKey::random();
+    let new = old
+        .to_builder()
+        .listen_port(51821)
+        .fwmark(51820)
+        .private_key(private_key.clone())
+        .build();
+
+    let commands = old.wg_set_commands(&new);
+
+    assert_eq!(
+        commands,
+        vec![
+            WgSetCommand::ListenPort(51821),
+            WgSetCommand::FwMark(51820),
+            WgSetCommand::PrivateKey(private_key.clone())
+        ]
+    );
+    assert_eq!(
+        commands[1].args("wg0"),
+        vec!["set", "wg0", "fwmark", "51820"]
+    );
+    assert_eq!(
+        commands[2].args("wg0"),
+        vec!["set", "wg0", "private-key", "/dev/stdin"]
+    );
+    assert_eq!(commands[2].stdin(), Some(private_key.to_string()));
+}
+
+#[test]
+fn wg_set_peers() {
+    let (old, laptop, phone) = get_example_data();
+
+    let tablet = PeerBuilder::new()
+        .add_allowed_ip(as_ipnet!("10.0.0.4/32"))
+        .endpoint("192.0.2.4:51820".to_string())
+        .build();
+    let mut new_laptop = laptop
+        .to_builder()
+        .add_allowed_ip(as_ipnet!("10.0.1.0/24"))
+        .name("Laptop".to_string())
+        .build();
+    new_laptop.preshared_key = None;
+
+    let new = old
+        .to_builder()
+        .set_peers(vec![new_laptop, tablet.clone()])
+        .build();
+
+    let commands = old.wg_set_commands(&new);
+
+    assert_eq!(commands.len(), 3);
+    assert_eq!(
+        commands[0].args("wg0"),
+        vec![
+            "set".to_string(),
+            "wg0".to_string(),
+            "peer".to_string(),
+            phone.public_key().to_string(),
+            "remove".to_string()
+        ]
+    );
+    assert_eq!(
+        commands[1].args("wg0"),
+        vec![
+            "set".to_string(),
+            "wg0".to_string(),
+            "peer".to_string(),
+            laptop.public_key().to_string(),
+            "allowed-ips".to_string(),
+            "10.0.0.2/32,10.0.1.0/24".to_string(),
+            "preshared-key".to_string(),
+            "/dev/null".to_string(),
+        ]
+    );
+    assert_eq!(commands[1].stdin(), None);
+    assert_eq!(
+        commands[2],
+        WgSetCommand::UpdatePeer(PeerUpdate {
+            public_key: tablet.public_key(),
+            endpoint: Some("192.0.2.4:51820".to_string()),
+            allowed_ips: Some(vec![as_ipnet!("10.0.0.4/32")]),
+            preshared_key: None,
+            persistent_keepalive: None,
+        })
+    );
+}
+
+#[test]
+fn wg_set_peer_keys() {
+    let (old, laptop, phone) = get_example_data();
+
+    let preshared_key = PresharedKey::random();
+    let mut new = old.clone();
+    new.peers[0].preshared_key = Some(preshared_key.clone());
+    new.peers[1].persistent_keepalive = None;
+
+    let commands = old.wg_set_commands(&new);
+
+    assert_eq!(
+        commands[0].args("wg0")[2..],
+        [
+            "peer".to_string(),
+            laptop.public_key().to_string(),
+            "preshared-key".to_string(),
+            "/dev/stdin".to_string(),
+        ]
+    );
+    assert_eq!(commands[0].stdin(), Some(preshared_key.to_string()));
+    assert_eq!(
+        commands[1].args("wg0")[2..],
+        [
+            "peer".to_string(),
+            phone.public_key().to_string(),
+            "persistent-keepalive".to_string(),
+            "off".to_string(),
+        ]
+    );
+}